@@ -7,7 +7,9 @@ use crate::ipv4addr::IPv4Addr;
 use crate::ipv6addr::IPv6Addr;
 use crate::macaddr::MacAddr;
 
-#[derive(PartialEq, Copy, Clone)]
+/// Any kind of address, which is an argument of format.
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[non_exhaustive]
 pub enum Addr {
     IPv4(IPv4Addr),
     IPv6(IPv6Addr),
@@ -50,6 +52,18 @@ impl From<u128> for Addr {
     }
 }
 
+/// Converts any address into a number, dropping the zone of IPv6 address.
+impl From<Addr> for u128 {
+    fn from(value: Addr) -> Self {
+        match value {
+            Addr::IPv4(value) => u32::from(value).into(),
+            Addr::IPv6(value) => value.into(),
+            Addr::Mac(value) => u64::from(value).into(),
+            Addr::Number(value) => value,
+        }
+    }
+}
+
 impl PartialOrd for Addr {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match self {
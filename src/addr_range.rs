@@ -1,3 +1,4 @@
+use std::convert::TryFrom;
 use std::ops::AddAssign;
 use std::ops::{Add, Sub};
 use std::str::FromStr;
@@ -6,23 +7,32 @@ use crate::addr::Addr;
 use crate::bundled_iter::{IterBundle, ResettableIterator};
 use crate::ipv4addr::IPv4Addr;
 use crate::ipv6addr::IPv6Addr;
-use crate::macaddr::MacAddr;
+use crate::macaddr::{MacAddr, MAC_MAX};
+use crate::number::parse_number;
+use crate::parse_error::{ParseError, ParseErrorKind};
 
-#[derive(Debug, PartialEq)]
+/// Range of addresses, which is specified as `START-END`, `START+OFFSET` or `ADDR/PREFIX`
+/// followed by optional `:STEP`.
+#[derive(Debug, PartialEq, Clone)]
 pub struct AddrRange<T> {
     start: T,
     end: T,
     overflow: bool,
+    step: u128,
 }
 
 impl<T> AddrRange<T> {
-    fn new(start: T, end: T) -> Self {
+    /// Creates a range from `start` to `end` inclusive, which is descending if
+    /// `start` is greater than `end`.
+    pub fn new(start: T, end: T) -> Self {
         AddrRange {
             start,
             end,
             overflow: false,
+            step: 1,
         }
     }
+
     fn is_ascending(&self) -> bool
     where
         T: PartialOrd,
@@ -34,21 +44,24 @@ impl<T> AddrRange<T> {
         }
     }
 
-    fn within(&self, value: T) -> bool
-    where
-        T: PartialOrd,
-    {
-        if self.overflow {
-            if self.start <= self.end {
-                value <= self.start || value >= self.end
-            } else {
-                value >= self.start || value <= self.end
-            }
-        } else if self.start <= self.end {
-            value >= self.start && value <= self.end
-        } else {
-            value <= self.start && value >= self.end
+    pub fn start(&self) -> &T {
+        &self.start
+    }
+
+    pub fn end(&self) -> &T {
+        &self.end
+    }
+
+    pub fn step(&self) -> u128 {
+        self.step
+    }
+
+    /// Returns the range which yields every `step`-th address, or `None` if `step` is zero.
+    pub fn with_step(self, step: u128) -> Option<Self> {
+        if step == 0 {
+            return None;
         }
+        Some(AddrRange { step, ..self })
     }
 
     pub fn into_range<S>(self) -> AddrRange<S>
@@ -59,73 +72,303 @@ impl<T> AddrRange<T> {
             start: self.start.into(),
             end: self.end.into(),
             overflow: self.overflow,
+            step: self.step,
+        }
+    }
+}
+
+impl<T> AddrRange<T>
+where
+    T: Rangeable,
+{
+    pub fn contains(&self, value: T) -> bool {
+        let (offset, distance) = if self.is_ascending() {
+            (value.diff(self.start), self.end.diff(self.start))
+        } else {
+            (self.start.diff(value), self.start.diff(self.end))
+        };
+        let offset = offset.into();
+        offset <= distance.into() && offset % self.step == 0
+    }
+
+    /// Returns the lowest and the highest addresses if the range has every address
+    /// between them.
+    fn block(&self) -> Option<(T, T)> {
+        if self.step != 1 || self.overflow {
+            None
+        } else if self.is_ascending() {
+            Some((self.start, self.end))
+        } else {
+            Some((self.end, self.start))
+        }
+    }
+}
+
+impl<T> AddrRange<T>
+where
+    T: Rangeable,
+{
+    /// Parses a positive step which fits in the address.
+    fn parse_step(value: &str) -> Option<u128> {
+        let step = parse_number::<T::Int>(value).ok()?.into();
+        if step == 0 {
+            None
+        } else {
+            Some(step)
+        }
+    }
+
+    fn parse_range(value: &str) -> Result<Self, ParseError> {
+        if let Some(i) = value.find('+') {
+            let start = T::parse(&value[0..i])?;
+            let (negative, offset) = match value[i + 1..].strip_prefix('-') {
+                Some(offset) => (true, offset),
+                None => (false, &value[i + 1..]),
+            };
+            let offset = parse_number::<T::Int>(offset).map_err(|e| {
+                e.shift(value.len() - offset.len())
+                    .with_kind(ParseErrorKind::InvalidOffset)
+            })?;
+            let end = if negative {
+                start - offset
+            } else {
+                start + offset
+            };
+            Ok(AddrRange {
+                start,
+                end,
+                overflow: if negative { start < end } else { start > end },
+                step: 1,
+            })
+        } else if let Some(i) = value.find('/') {
+            let addr = T::parse(&value[0..i])?;
+            let prefix_len_max = T::PREFIX_LEN_MAX
+                .ok_or_else(|| ParseError::new(ParseErrorKind::UnexpectedChar, i))?;
+            let (prefix_len, hosts) = match value[i + 1..].strip_suffix('h') {
+                Some(prefix_len) => (prefix_len, true),
+                None => (&value[i + 1..], false),
+            };
+            let prefix_len = u32::from_str(prefix_len)
+                .map_err(|_| ParseError::new(ParseErrorKind::InvalidPrefix, i + 1))?;
+            if prefix_len > prefix_len_max.into() {
+                return Err(ParseError::new(ParseErrorKind::PrefixOutOfRange, i + 1));
+            }
+            let range = if hosts {
+                // Only IPv4 prefix has the network and the broadcast addresses.
+                addr.host_range(prefix_len as u8).ok_or_else(|| {
+                    ParseError::new(ParseErrorKind::UnexpectedChar, value.len() - 1)
+                })?
+            } else {
+                addr.prefix_range(prefix_len as u8)
+                    .expect("prefix length is checked")
+            };
+            Ok(AddrRange::new(range.0, range.1))
+        } else {
+            let err = match T::parse(value) {
+                Ok(start) => return Ok(AddrRange::new(start, start)),
+                Err(err) => err,
+            };
+            // MAC address may contain '-' as well, so try every '-' as the separator.
+            // If no end address is valid, the error of the first valid start address
+            // is reported, since it is likely the separator.
+            let mut end_err = None;
+            for (i, _) in value.match_indices('-') {
+                if let Ok(start) = T::parse(&value[0..i]) {
+                    match T::parse(&value[i + 1..]) {
+                        Ok(end) if start.is_same_scope(end) => {
+                            return Ok(AddrRange::new(start, end))
+                        }
+                        Ok(_) => {
+                            end_err.get_or_insert(ParseError::new(
+                                ParseErrorKind::ZoneMismatch,
+                                i + 1,
+                            ));
+                        }
+                        Err(e) => {
+                            end_err.get_or_insert(e.shift(i + 1));
+                        }
+                    }
+                }
+            }
+            Err(end_err.unwrap_or(err))
         }
     }
 }
 
 impl<T> FromStr for AddrRange<T>
 where
-    T: Copy + FromStr + Rangeable,
+    T: Rangeable,
 {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        if let Some(i) = value.find("+") {
-            if i < value.len() {
-                let start = T::from_str(&value[0..i]).map_err(|_| ())?;
-                let negative = &value[i + 1..i + 2] == "-";
-                let end = if negative {
-                    start - <T as Rangeable>::Int::from_str(&value[i + 2..]).map_err(|_| ())?
-                } else {
-                    start + <T as Rangeable>::Int::from_str(&value[i + 1..]).map_err(|_| ())?
-                };
-                return Ok(AddrRange {
-                    start,
-                    end,
-                    overflow: if negative { start < end } else { start > end },
-                });
-            }
-        } else if let Some(i) = value.find("-") {
-            if i < value.len() {
-                let start = T::from_str(&value[0..i]).map_err(|_| ())?;
-                let end = T::from_str(&value[i + 1..]).map_err(|_| ())?;
-                return Ok(AddrRange::new(start, end));
+        if let Some(bracketed) = value.strip_prefix('[') {
+            let i = bracketed
+                .find(']')
+                .ok_or_else(|| ParseError::new(ParseErrorKind::UnexpectedChar, value.len()))?;
+            let mut range = AddrRange::parse_range(&bracketed[..i]).map_err(|e| e.shift(1))?;
+            let rest = &bracketed[i + 1..];
+            if !rest.is_empty() {
+                let step = rest
+                    .strip_prefix(':')
+                    .ok_or_else(|| ParseError::new(ParseErrorKind::UnexpectedChar, i + 2))?;
+                range.step = AddrRange::<T>::parse_step(step)
+                    .ok_or_else(|| ParseError::new(ParseErrorKind::InvalidStep, i + 3))?;
             }
-        } else {
-            let start = T::from_str(value).map_err(|_| ())?;
-            return Ok(AddrRange::new(start, start));
+            return Ok(range);
         }
-        Err(())
+        // MAC and IPv6 addresses contain ':' as well, so the value is read without the step
+        // if possible. Such a range needs brackets to have the step, such as `[::1-::10]:4`.
+        let whole = AddrRange::parse_range(value);
+        let stepped = value
+            .rfind(':')
+            .and_then(|i| Some((i, AddrRange::parse_range(&value[0..i]).ok()?)));
+        let (i, mut range) = match (whole, stepped) {
+            (Err(_), Some(stepped)) => stepped,
+            (whole, _) => return whole,
+        };
+        range.step = AddrRange::<T>::parse_step(&value[i + 1..])
+            .ok_or_else(|| ParseError::new(ParseErrorKind::InvalidStep, i + 1))?;
+        Ok(range)
     }
 }
 
+/// Address which can be an item of `AddrRange`.
 pub trait Rangeable:
     Copy
     + PartialOrd
     + Add<<Self as Rangeable>::Int, Output = Self>
     + Sub<<Self as Rangeable>::Int, Output = Self>
+    + From<<Self as Rangeable>::Int>
 {
-    type Int: Copy + Into<u128> + From<u32> + AddAssign + FromStr;
+    type Int: Copy + Into<u128> + TryFrom<u128> + From<u32> + AddAssign;
+
+    /// Parses an address. Defaults to a number, which may be prefixed unlike `FromStr`
+    /// of primitive integers.
+    fn parse(value: &str) -> Result<Self, ParseError> {
+        parse_number::<Self::Int>(value).map(Self::from)
+    }
+
+    /// Returns `self - other` wrapping around the address space.
+    fn diff(self, other: Self) -> Self::Int;
+
+    /// Returns whether `self` and `other` are in the same scope, so that they can be
+    /// the start and the end of a range.
+    fn is_same_scope(self, _other: Self) -> bool {
+        true
+    }
+
+    /// Maximum prefix length of `ADDR/PREFIX`, or `None` if the type has no prefix notation.
+    const PREFIX_LEN_MAX: Option<u8> = None;
+
+    /// Returns the first and the last addresses of the prefix `self/prefix_len`,
+    /// or `None` if the type has no prefix notation or `prefix_len` is out of bounds.
+    fn prefix_range(self, _prefix_len: u8) -> Option<(Self, Self)> {
+        None
+    }
+
+    /// Same as `prefix_range`, but excludes the network and the broadcast addresses
+    /// if the prefix has them.
+    fn host_range(self, _prefix_len: u8) -> Option<(Self, Self)> {
+        None
+    }
 }
 
 impl Rangeable for MacAddr {
     type Int = u64;
+
+    fn parse(value: &str) -> Result<Self, ParseError> {
+        MacAddr::from_str(value)
+    }
+
+    fn diff(self, other: Self) -> Self::Int {
+        u64::from(self).wrapping_sub(u64::from(other)) & MAC_MAX
+    }
 }
 
 impl Rangeable for IPv4Addr {
     type Int = u32;
+
+    const PREFIX_LEN_MAX: Option<u8> = Some(32);
+
+    fn parse(value: &str) -> Result<Self, ParseError> {
+        IPv4Addr::from_str(value)
+    }
+
+    fn diff(self, other: Self) -> Self::Int {
+        u32::from(self).wrapping_sub(u32::from(other))
+    }
+
+    fn prefix_range(self, prefix_len: u8) -> Option<(Self, Self)> {
+        if prefix_len > 32 {
+            return None;
+        }
+        let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+        let n = u32::from(self) & mask;
+        Some((IPv4Addr::from(n), IPv4Addr::from(n | !mask)))
+    }
+
+    fn host_range(self, prefix_len: u8) -> Option<(Self, Self)> {
+        let (network, broadcast) = self.prefix_range(prefix_len)?;
+        // Point-to-point link (RFC 3021) and host route have no network and broadcast addresses.
+        if prefix_len >= 31 {
+            Some((network, broadcast))
+        } else {
+            Some((network + 1u32, broadcast - 1u32))
+        }
+    }
 }
 
 impl Rangeable for IPv6Addr {
     type Int = u128;
+
+    const PREFIX_LEN_MAX: Option<u8> = Some(128);
+
+    fn parse(value: &str) -> Result<Self, ParseError> {
+        IPv6Addr::from_str(value)
+    }
+
+    fn diff(self, other: Self) -> Self::Int {
+        u128::from(self).wrapping_sub(u128::from(other))
+    }
+
+    /// The same address with different zones would be ordered by the zone.
+    fn is_same_scope(self, other: Self) -> bool {
+        self.zone() == other.zone()
+    }
+
+    fn prefix_range(self, prefix_len: u8) -> Option<(Self, Self)> {
+        if prefix_len > 128 {
+            return None;
+        }
+        let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+        // Arithmetic keeps the zone.
+        let start = self - (u128::from(self) & !mask);
+        Some((start, start + !mask))
+    }
 }
 
 impl Rangeable for Addr {
-    type Int = u64;
+    type Int = u128;
+
+    fn diff(self, other: Self) -> Self::Int {
+        match (self, other) {
+            (Addr::IPv4(value), Addr::IPv4(ovalue)) => value.diff(ovalue).into(),
+            (Addr::IPv6(value), Addr::IPv6(ovalue)) => value.diff(ovalue),
+            (Addr::Mac(value), Addr::Mac(ovalue)) => value.diff(ovalue).into(),
+            (Addr::Number(value), Addr::Number(ovalue)) => value.diff(ovalue),
+            // Ranges never mix address types.
+            _ => 0,
+        }
+    }
 }
 
 impl Rangeable for u128 {
     type Int = u128;
+
+    fn diff(self, other: Self) -> Self::Int {
+        self.wrapping_sub(other)
+    }
 }
 
 pub struct AddrRangeIter<T>
@@ -133,7 +376,7 @@ where
     T: Rangeable,
 {
     range: AddrRange<T>,
-    offset: T::Int,
+    offset: Option<T::Int>,
 }
 
 impl<T> IntoIterator for AddrRange<T>
@@ -146,7 +389,7 @@ where
     fn into_iter(self) -> Self::IntoIter {
         AddrRangeIter {
             range: self,
-            offset: 0.into(),
+            offset: Some(0.into()),
         }
     }
 }
@@ -158,17 +401,49 @@ where
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut result = None;
-        let n = if self.range.is_ascending() {
-            self.range.start + self.offset
+        let mut offset = self.offset?;
+        let (n, remaining) = if self.range.is_ascending() {
+            let n = self.range.start + offset;
+            (n, self.range.end.diff(n))
+        } else {
+            let n = self.range.start - offset;
+            (n, n.diff(self.range.end))
+        };
+        // Stop before stepping over the end address, so that the offset never overflows
+        // even if the range covers the whole address space.
+        self.offset = if remaining.into() < self.range.step {
+            None
         } else {
-            self.range.start - self.offset
+            T::Int::try_from(self.range.step).ok().map(|step| {
+                offset += step;
+                offset
+            })
         };
-        if self.range.within(n) {
-            result = Some(n);
+        Some(n)
+    }
+}
+
+impl<T> AddrRangeIter<T>
+where
+    T: Rangeable,
+{
+    /// Skips the addresses in `lo..=hi` following `n`, which is the last yielded one
+    /// and within them.
+    fn skip_block(&mut self, n: T, (lo, hi): (T, T)) {
+        if self.offset.is_none() {
+            return;
         }
-        self.offset += 1.into();
-        return result;
+        let range = &self.range;
+        let (offset, skipped, distance) = if range.is_ascending() {
+            (n.diff(range.start), hi.diff(n), range.end.diff(range.start))
+        } else {
+            (range.start.diff(n), n.diff(lo), range.start.diff(range.end))
+        };
+        let offset = (skipped.into() / range.step + 1)
+            .checked_mul(range.step)
+            .and_then(|skip| skip.checked_add(offset.into()))
+            .filter(|offset| *offset <= distance.into());
+        self.offset = offset.and_then(|offset| T::Int::try_from(offset).ok());
     }
 }
 
@@ -177,19 +452,274 @@ where
     T: Rangeable,
 {
     fn reset(&mut self) {
-        self.offset = 0.into();
+        self.offset = Some(0.into());
+    }
+}
+
+/// Union of ranges, which is specified as comma (`,`) separated ranges.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AddrRangeList<T> {
+    ranges: Vec<AddrRange<T>>,
+}
+
+impl<T> AddrRangeList<T> {
+    /// Appends a range, which is iterated after the existing ones.
+    pub fn push(&mut self, range: AddrRange<T>) {
+        self.ranges.push(range);
+    }
+
+    pub fn into_range<S>(self) -> AddrRangeList<S>
+    where
+        T: Into<S>,
+    {
+        AddrRangeList {
+            ranges: self.ranges.into_iter().map(|r| r.into_range()).collect(),
+        }
+    }
+}
+
+impl<T> From<AddrRange<T>> for AddrRangeList<T> {
+    fn from(range: AddrRange<T>) -> Self {
+        AddrRangeList {
+            ranges: vec![range],
+        }
+    }
+}
+
+impl<T> FromStr for AddrRangeList<T>
+where
+    T: Rangeable,
+{
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut start = 0;
+        let mut ranges = vec![];
+        for range in value.split(',') {
+            ranges.push(AddrRange::from_str(range).map_err(|e| e.shift(start))?);
+            start += range.len() + 1;
+        }
+        Ok(AddrRangeList { ranges })
+    }
+}
+
+pub struct AddrRangeListIter<T>
+where
+    T: Rangeable,
+{
+    iters: Vec<AddrRangeIter<T>>,
+    index: usize,
+}
+
+impl<T> IntoIterator for AddrRangeList<T>
+where
+    T: Rangeable,
+{
+    type Item = T;
+    type IntoIter = AddrRangeListIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        AddrRangeListIter {
+            iters: self.ranges.into_iter().map(|r| r.into_iter()).collect(),
+            index: 0,
+        }
+    }
+}
+
+impl<T> Iterator for AddrRangeListIter<T>
+where
+    T: Rangeable,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(iter) = self.iters.get_mut(self.index) {
+            if let Some(n) = iter.next() {
+                return Some(n);
+            }
+            self.index += 1;
+        }
+        None
+    }
+}
+
+impl<T> AddrRangeListIter<T>
+where
+    T: Rangeable,
+{
+    fn skip_block(&mut self, n: T, block: (T, T)) {
+        if let Some(iter) = self.iters.get_mut(self.index) {
+            iter.skip_block(n, block);
+        }
+    }
+}
+
+impl<T> ResettableIterator for AddrRangeListIter<T>
+where
+    T: Rangeable,
+{
+    fn reset(&mut self) {
+        for iter in &mut self.iters {
+            iter.reset();
+        }
+        self.index = 0;
+    }
+}
+
+impl<T> AddrRangeList<T>
+where
+    T: Rangeable,
+{
+    pub fn contains(&self, value: T) -> bool {
+        self.ranges.iter().any(|r| r.contains(value))
+    }
+}
+
+/// Range list with exclusions, which are specified as exclamation mark (`!`) separated
+/// range lists following the first one.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AddrRangeSet<T> {
+    list: AddrRangeList<T>,
+    excludes: Vec<AddrRangeList<T>>,
+}
+
+impl<T> AddrRangeSet<T> {
+    /// Excludes the addresses in `list` from the set.
+    pub fn exclude(&mut self, list: AddrRangeList<T>) {
+        self.excludes.push(list);
+    }
+
+    pub fn into_range<S>(self) -> AddrRangeSet<S>
+    where
+        T: Into<S>,
+    {
+        AddrRangeSet {
+            list: self.list.into_range(),
+            excludes: self.excludes.into_iter().map(|l| l.into_range()).collect(),
+        }
+    }
+}
+
+impl<T> From<AddrRange<T>> for AddrRangeSet<T> {
+    fn from(range: AddrRange<T>) -> Self {
+        AddrRangeList::from(range).into()
+    }
+}
+
+impl<T> From<AddrRangeList<T>> for AddrRangeSet<T> {
+    fn from(list: AddrRangeList<T>) -> Self {
+        AddrRangeSet {
+            list,
+            excludes: vec![],
+        }
+    }
+}
+
+impl<T> AddrRangeSet<T>
+where
+    T: Rangeable,
+{
+    pub fn contains(&self, value: T) -> bool {
+        self.list.contains(value) && !self.excludes.iter().any(|l| l.contains(value))
+    }
+}
+
+impl<T> FromStr for AddrRangeSet<T>
+where
+    T: Rangeable,
+{
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut start = 0;
+        let mut lists = vec![];
+        for list in value.split('!') {
+            lists.push(AddrRangeList::from_str(list).map_err(|e| e.shift(start))?);
+            start += list.len() + 1;
+        }
+        let list = lists.remove(0);
+        Ok(AddrRangeSet {
+            list,
+            excludes: lists,
+        })
+    }
+}
+
+pub struct AddrRangeSetIter<T>
+where
+    T: Rangeable,
+{
+    iter: AddrRangeListIter<T>,
+    excludes: Vec<AddrRangeList<T>>,
+}
+
+impl<T> IntoIterator for AddrRangeSet<T>
+where
+    T: Rangeable,
+{
+    type Item = T;
+    type IntoIter = AddrRangeSetIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        AddrRangeSetIter {
+            iter: self.list.into_iter(),
+            excludes: self.excludes,
+        }
+    }
+}
+
+impl<T> Iterator for AddrRangeSetIter<T>
+where
+    T: Rangeable,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let n = self.iter.next()?;
+            let excluded = self
+                .excludes
+                .iter()
+                .flat_map(|l| &l.ranges)
+                .find(|r| r.contains(n));
+            match excluded {
+                None => return Some(n),
+                // Jumps over the excluded addresses at once if they are consecutive.
+                Some(range) => {
+                    if let Some(block) = range.block() {
+                        self.iter.skip_block(n, block);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T> ResettableIterator for AddrRangeSetIter<T>
+where
+    T: Rangeable,
+{
+    fn reset(&mut self) {
+        self.iter.reset();
     }
 }
 
-pub type AddrRanges<T> = IterBundle<AddrRangeIter<T>>;
+pub type AddrRanges<T> = IterBundle<AddrRangeSetIter<T>>;
 
 #[cfg(test)]
 mod tests {
-    use super::{AddrRange, AddrRanges};
+    use super::{AddrRange, AddrRangeList, AddrRangeSet, AddrRanges};
+    use crate::bundled_iter::ResettableIterator;
     use crate::ipv4addr::IPv4Addr;
+    use crate::ipv6addr::IPv6Addr;
     use crate::macaddr::MacAddr;
+    use crate::parse_error::{ParseError, ParseErrorKind};
     use std::str::FromStr;
 
+    fn err<T>(kind: ParseErrorKind, offset: usize) -> Result<T, ParseError> {
+        Err(ParseError::new(kind, offset))
+    }
+
     #[test]
     fn addr_range_from_str_with_2macs() {
         assert_eq!(
@@ -240,37 +770,72 @@ mod tests {
     fn addr_range_from_str_err() {
         assert_eq!(
             AddrRange::<MacAddr>::from_str("00:11:22:33:44:55-"),
-            Err(())
+            err(ParseErrorKind::Empty, 18)
+        );
+        assert_eq!(
+            AddrRange::<MacAddr>::from_str("0-1-2"),
+            err(ParseErrorKind::GroupLength, 2)
         );
-        assert_eq!(AddrRange::<MacAddr>::from_str("0-1-2"), Err(()));
     }
 
     #[test]
-    fn addr_range_from_str_with_ipv4() {
+    fn addr_range_from_str_with_hyphen_macs() {
         assert_eq!(
-            AddrRange::<IPv4Addr>::from_str("192.168.0.1-192.168.0.10"),
+            AddrRange::<MacAddr>::from_str("aa-bb-cc-dd-ee-00"),
             Ok(AddrRange::new(
-                IPv4Addr::new(192, 168, 0, 1),
-                IPv4Addr::new(192, 168, 0, 10)
+                MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0),
+                MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0),
             ))
         );
-    }
-
-    #[test]
-    fn addr_range_from_str_with_plus() {
         assert_eq!(
-            AddrRange::<IPv4Addr>::from_str("192.168.0.1+10"),
+            AddrRange::<MacAddr>::from_str("aa-bb-cc-dd-ee-00-aa-bb-cc-dd-ee-10"),
             Ok(AddrRange::new(
-                IPv4Addr::new(192, 168, 0, 1),
-                IPv4Addr::new(192, 168, 0, 11)
+                MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0),
+                MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x10),
             ))
         );
-    }
-
-    #[test]
-    fn addr_range_from_str_with_plus_descending() {
         assert_eq!(
-            AddrRange::<IPv4Addr>::from_str("192.168.0.10+-9"),
+            AddrRange::<MacAddr>::from_str("aabb.ccdd.ee00-AA-BB-CC-DD-EE-10"),
+            Ok(AddrRange::new(
+                MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0),
+                MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x10),
+            ))
+        );
+        assert_eq!(
+            AddrRange::<MacAddr>::from_str("aa-bb-cc-dd-ee-00+16"),
+            Ok(AddrRange::new(
+                MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0),
+                MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0x10),
+            ))
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_ipv4() {
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("192.168.0.1-192.168.0.10"),
+            Ok(AddrRange::new(
+                IPv4Addr::new(192, 168, 0, 1),
+                IPv4Addr::new(192, 168, 0, 10)
+            ))
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_plus() {
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("192.168.0.1+10"),
+            Ok(AddrRange::new(
+                IPv4Addr::new(192, 168, 0, 1),
+                IPv4Addr::new(192, 168, 0, 11)
+            ))
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_plus_descending() {
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("192.168.0.10+-9"),
             Ok(AddrRange::new(
                 IPv4Addr::new(192, 168, 0, 10),
                 IPv4Addr::new(192, 168, 0, 1)
@@ -282,7 +847,367 @@ mod tests {
     fn addr_range_from_str_with_plus_err() {
         assert_eq!(
             AddrRange::<IPv4Addr>::from_str("192.168.0.1+192.168.0.10"),
-            Err(())
+            err(ParseErrorKind::InvalidOffset, 15)
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_ipv4_prefix() {
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("192.168.0.0/24"),
+            Ok(AddrRange::new(
+                IPv4Addr::new(192, 168, 0, 0),
+                IPv4Addr::new(192, 168, 0, 255)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.1.2.3/8"),
+            Ok(AddrRange::new(
+                IPv4Addr::new(10, 0, 0, 0),
+                IPv4Addr::new(10, 255, 255, 255)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.1.2.3/32"),
+            Ok(AddrRange::new(
+                IPv4Addr::new(10, 1, 2, 3),
+                IPv4Addr::new(10, 1, 2, 3)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.1.2.3/0"),
+            Ok(AddrRange::new(
+                IPv4Addr::new(0, 0, 0, 0),
+                IPv4Addr::new(255, 255, 255, 255)
+            ))
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_ipv6_prefix() {
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("2001:db8::/120"),
+            Ok(AddrRange::new(
+                IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0),
+                IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0xff)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("fe80::1/64"),
+            Ok(AddrRange::new(
+                IPv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0),
+                IPv6Addr::new(0xfe80, 0, 0, 0, 0xffff, 0xffff, 0xffff, 0xffff)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("::1/128"),
+            Ok(AddrRange::new(
+                IPv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1),
+                IPv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1)
+            ))
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_ipv6_zone() {
+        let range = AddrRange::<IPv6Addr>::from_str("fe80::%eth0/126:2").unwrap();
+        assert_eq!(
+            range.into_iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+            vec!["fe80::%eth0", "fe80::2%eth0"]
+        );
+        let range = AddrRange::<IPv6Addr>::from_str("fe80::1%eth0-fe80::2%eth0").unwrap();
+        assert_eq!(
+            range.into_iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+            vec!["fe80::1%eth0", "fe80::2%eth0"]
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_ipv6_zone_err() {
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("fe80::1%eth1-fe80::1%eth0"),
+            err(ParseErrorKind::ZoneMismatch, 13)
+        );
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("fe80::1%eth0-fe80::2"),
+            err(ParseErrorKind::ZoneMismatch, 13)
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_prefix_err() {
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0/33"),
+            err(ParseErrorKind::PrefixOutOfRange, 9)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0/300"),
+            err(ParseErrorKind::PrefixOutOfRange, 9)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0/"),
+            err(ParseErrorKind::InvalidPrefix, 9)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0/-1"),
+            err(ParseErrorKind::InvalidPrefix, 9)
+        );
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("2001:db8::/129"),
+            err(ParseErrorKind::PrefixOutOfRange, 11)
+        );
+        assert_eq!(
+            AddrRange::<MacAddr>::from_str("0/24"),
+            err(ParseErrorKind::UnexpectedChar, 1)
+        );
+        assert_eq!(
+            AddrRange::<u128>::from_str("0/24"),
+            err(ParseErrorKind::UnexpectedChar, 1)
+        );
+    }
+
+    #[test]
+    fn addr_range_iter_prefix() {
+        let range = AddrRange::<IPv4Addr>::from_str("192.168.0.0/30").unwrap();
+        let mut iter = range.into_iter();
+        assert_eq!(iter.next(), Some(IPv4Addr::new(192, 168, 0, 0)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(192, 168, 0, 1)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(192, 168, 0, 2)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(192, 168, 0, 3)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn addr_range_iter_last_prefix() {
+        let range =
+            AddrRange::<IPv6Addr>::from_str("ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe/127").unwrap();
+        let mut iter = range.into_iter();
+        assert_eq!(
+            iter.next(),
+            Some(IPv6Addr::new(
+                0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xfffe
+            ))
+        );
+        assert_eq!(
+            iter.next(),
+            Some(IPv6Addr::new(
+                0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff
+            ))
+        );
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn addr_range_from_str_with_ipv4_hosts() {
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0/29h"),
+            Ok(AddrRange::new(
+                IPv4Addr::new(10, 0, 0, 1),
+                IPv4Addr::new(10, 0, 0, 6)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0/30h"),
+            Ok(AddrRange::new(
+                IPv4Addr::new(10, 0, 0, 1),
+                IPv4Addr::new(10, 0, 0, 2)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.1/31h"),
+            Ok(AddrRange::new(
+                IPv4Addr::new(10, 0, 0, 0),
+                IPv4Addr::new(10, 0, 0, 1)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.1/32h"),
+            Ok(AddrRange::new(
+                IPv4Addr::new(10, 0, 0, 1),
+                IPv4Addr::new(10, 0, 0, 1)
+            ))
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_hosts_err() {
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0/33h"),
+            err(ParseErrorKind::PrefixOutOfRange, 9)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0/h"),
+            err(ParseErrorKind::InvalidPrefix, 9)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0/24hh"),
+            err(ParseErrorKind::InvalidPrefix, 9)
+        );
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("2001:db8::/64h"),
+            err(ParseErrorKind::UnexpectedChar, 13)
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_step() {
+        let mut range = AddrRange::new(IPv4Addr::new(10, 0, 0, 0), IPv4Addr::new(10, 0, 0, 255));
+        range.step = 4;
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0-10.0.0.255:4"),
+            Ok(range)
+        );
+
+        let mut range = AddrRange::new(
+            MacAddr::new(0xaa, 0xbb, 0xcc, 0, 0, 0),
+            MacAddr::new(0xaa, 0xbb, 0xcc, 0, 0, 0x40),
+        );
+        range.step = 16;
+        assert_eq!(
+            AddrRange::<MacAddr>::from_str("aa:bb:cc:00:00:00+64:16"),
+            Ok(range)
+        );
+
+        let mut range = AddrRange::new(
+            IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0),
+            IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0xff),
+        );
+        range.step = 0x40;
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("2001:db8::/120:64"),
+            Ok(range)
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_prefixed_numbers() {
+        assert_eq!(
+            AddrRange::<u128>::from_str("0x10-0o20"),
+            Ok(AddrRange::new(16, 16))
+        );
+        assert_eq!(
+            AddrRange::<u128>::from_str("0b1+0xf"),
+            Ok(AddrRange::new(1, 16))
+        );
+
+        let mut range = AddrRange::new(
+            MacAddr::new(0xaa, 0xbb, 0xcc, 0, 0, 0),
+            MacAddr::new(0xaa, 0xbb, 0xcc, 0, 0, 0x40),
+        );
+        range.step = 16;
+        assert_eq!(
+            AddrRange::<MacAddr>::from_str("aa:bb:cc:00:00:00+0x40:0x10"),
+            Ok(range)
+        );
+        assert_eq!(
+            AddrRange::<u128>::from_str("0x"),
+            err(ParseErrorKind::Empty, 2)
+        );
+        assert_eq!(
+            AddrRange::<u128>::from_str("0b2"),
+            err(ParseErrorKind::InvalidDigit, 2)
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_ipv6_ambiguous_step() {
+        // The suffix is a part of the end address if the whole value is a range.
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("::1-::10:4"),
+            Ok(AddrRange::new(
+                IPv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1),
+                IPv6Addr::new(0, 0, 0, 0, 0, 0, 0x10, 4)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("2001:db8::1-2001:db8::1:10"),
+            Ok(AddrRange::new(
+                IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1),
+                IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 1, 0x10)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("[::1-::10:4]"),
+            Ok(AddrRange::new(
+                IPv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1),
+                IPv6Addr::new(0, 0, 0, 0, 0, 0, 0x10, 4)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("[::1-::10]:4").map(|r| r.step()),
+            Ok(4)
+        );
+        // A single address is read as is as well.
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("fe80::1:2"),
+            Ok(AddrRange::new(
+                IPv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 1, 2),
+                IPv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 1, 2)
+            ))
+        );
+        assert_eq!(
+            AddrRange::<IPv6Addr>::from_str("::1-::10:0"),
+            Ok(AddrRange::new(
+                IPv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1),
+                IPv6Addr::new(0, 0, 0, 0, 0, 0, 0x10, 0)
+            ))
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_bracketed() {
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("[10.0.0.0-10.0.0.15]:4"),
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0-10.0.0.15:4")
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("[10.0.0.0/30]"),
+            AddrRange::<IPv4Addr>::from_str("10.0.0.0/30")
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("[10.0.0.0-10.0.0.15"),
+            err(ParseErrorKind::UnexpectedChar, 19)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("[10.0.0.0]4"),
+            err(ParseErrorKind::UnexpectedChar, 10)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("[10.0.0.0]:0"),
+            err(ParseErrorKind::InvalidStep, 11)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("[10.0.0.0-10.0.0.x]"),
+            err(ParseErrorKind::InvalidDigit, 17)
+        );
+    }
+
+    #[test]
+    fn addr_range_from_str_with_step_err() {
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("1-10:0"),
+            err(ParseErrorKind::InvalidStep, 5)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("1-10:"),
+            err(ParseErrorKind::InvalidStep, 5)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("1-10:a"),
+            err(ParseErrorKind::InvalidStep, 5)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("1-10:4294967296"),
+            err(ParseErrorKind::InvalidStep, 5)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("1-10:2:2"),
+            err(ParseErrorKind::InvalidDigit, 4)
+        );
+        assert_eq!(
+            AddrRange::<IPv4Addr>::from_str("1+"),
+            err(ParseErrorKind::InvalidOffset, 2)
         );
     }
 
@@ -328,11 +1253,287 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn addr_range_iter_step_ascending() {
+        let range = AddrRange::<MacAddr>::from_str("10-20:4").unwrap();
+        let mut iter = range.into_iter();
+        assert_eq!(iter.next(), Some(MacAddr::new(0, 0, 0, 0, 0, 10)));
+        assert_eq!(iter.next(), Some(MacAddr::new(0, 0, 0, 0, 0, 14)));
+        assert_eq!(iter.next(), Some(MacAddr::new(0, 0, 0, 0, 0, 18)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn addr_range_iter_step_descending() {
+        let range = AddrRange::<IPv4Addr>::from_str("0.0.0.20-0.0.0.10:5").unwrap();
+        let mut iter = range.into_iter();
+        assert_eq!(iter.next(), Some(IPv4Addr::new(0, 0, 0, 20)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(0, 0, 0, 15)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(0, 0, 0, 10)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn addr_range_iter_step_overflow() {
+        let range = AddrRange::<IPv4Addr>::from_str("255.255.255.250+10:4").unwrap();
+        let mut iter = range.into_iter();
+        assert_eq!(iter.next(), Some(IPv4Addr::new(255, 255, 255, 250)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(255, 255, 255, 254)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(0, 0, 0, 2)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn addr_range_iter_step_underflow() {
+        let range = AddrRange::<MacAddr>::from_str("00:00:00:00:00:02+-5:2").unwrap();
+        let mut iter = range.into_iter();
+        assert_eq!(iter.next(), Some(MacAddr::new(0, 0, 0, 0, 0, 2)));
+        assert_eq!(iter.next(), Some(MacAddr::new(0, 0, 0, 0, 0, 0)));
+        assert_eq!(
+            iter.next(),
+            Some(MacAddr::new(0xff, 0xff, 0xff, 0xff, 0xff, 0xfe))
+        );
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn addr_range_iter_step_whole_space() {
+        let range = AddrRange::<u128>::from_str(
+            "0-340282366920938463463374607431768211455:170141183460469231731687303715884105728",
+        )
+        .unwrap();
+        let mut iter = range.into_iter();
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), Some(1 << 127));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn addr_range_iter_step_reset() {
+        let range = AddrRange::<IPv6Addr>::from_str("2001:db8::/126:2").unwrap();
+        let mut iter = range.into_iter();
+        assert_eq!(
+            iter.next(),
+            Some(IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0))
+        );
+        assert_eq!(
+            iter.next(),
+            Some(IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2))
+        );
+        assert_eq!(iter.next(), None);
+        iter.reset();
+        assert_eq!(
+            iter.next(),
+            Some(IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn addr_range_list_from_str() {
+        assert_eq!(
+            AddrRangeList::<IPv4Addr>::from_str("10.0.0.1,10.0.0.5-10.0.0.9,10.0.1.0/30"),
+            Ok(AddrRangeList {
+                ranges: vec![
+                    AddrRange::new(IPv4Addr::new(10, 0, 0, 1), IPv4Addr::new(10, 0, 0, 1)),
+                    AddrRange::new(IPv4Addr::new(10, 0, 0, 5), IPv4Addr::new(10, 0, 0, 9)),
+                    AddrRange::new(IPv4Addr::new(10, 0, 1, 0), IPv4Addr::new(10, 0, 1, 3)),
+                ]
+            })
+        );
+        assert_eq!(
+            AddrRangeList::<MacAddr>::from_str("aa:bb:cc:dd:ee:ff"),
+            Ok(AddrRangeList::from(AddrRange::new(
+                MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff),
+                MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff),
+            )))
+        );
+    }
+
+    #[test]
+    fn addr_range_list_from_str_err() {
+        assert_eq!(
+            AddrRangeList::<IPv4Addr>::from_str(""),
+            err(ParseErrorKind::Empty, 0)
+        );
+        assert_eq!(
+            AddrRangeList::<IPv4Addr>::from_str("1,"),
+            err(ParseErrorKind::Empty, 2)
+        );
+        assert_eq!(
+            AddrRangeList::<IPv4Addr>::from_str(",1"),
+            err(ParseErrorKind::Empty, 0)
+        );
+        assert_eq!(
+            AddrRangeList::<IPv4Addr>::from_str("1,,2"),
+            err(ParseErrorKind::Empty, 2)
+        );
+        assert_eq!(
+            AddrRangeList::<IPv4Addr>::from_str("1,2-"),
+            err(ParseErrorKind::Empty, 4)
+        );
+    }
+
+    #[test]
+    fn addr_range_list_iter() {
+        let list =
+            AddrRangeList::<IPv4Addr>::from_str("10.0.0.1,10.0.0.5-10.0.0.7,10.0.1.0/31").unwrap();
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(IPv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(10, 0, 0, 5)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(10, 0, 0, 6)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(10, 0, 0, 7)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(10, 0, 1, 0)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(10, 0, 1, 1)));
+        assert_eq!(iter.next(), None);
+        iter.reset();
+        assert_eq!(iter.next(), Some(IPv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(10, 0, 0, 5)));
+    }
+
+    #[test]
+    fn addr_range_ranges_iter_list() {
+        let mut ranges = AddrRanges::<u128>::new();
+        ranges.push(AddrRangeSet::<u128>::from_str("1,5-6").unwrap());
+        ranges.push(AddrRangeSet::<u128>::from_str("10,20").unwrap());
+        let ranges_iter = ranges.into_iter();
+        assert_eq!(
+            ranges_iter.collect::<Vec<_>>(),
+            vec![vec![1, 10], vec![5, 20], vec![6, 10]]
+        );
+    }
+
+    #[test]
+    fn addr_range_contains() {
+        let range = AddrRange::<u128>::from_str("10-20").unwrap();
+        assert!(!range.contains(9));
+        assert!(range.contains(10));
+        assert!(range.contains(15));
+        assert!(range.contains(20));
+        assert!(!range.contains(21));
+
+        let range = AddrRange::<u128>::from_str("20-10:5").unwrap();
+        assert!(range.contains(20));
+        assert!(!range.contains(19));
+        assert!(range.contains(15));
+        assert!(range.contains(10));
+        assert!(!range.contains(5));
+
+        let range = AddrRange::<IPv4Addr>::from_str("255.255.255.255+2").unwrap();
+        assert!(!range.contains(IPv4Addr::new(255, 255, 255, 254)));
+        assert!(range.contains(IPv4Addr::new(255, 255, 255, 255)));
+        assert!(range.contains(IPv4Addr::new(0, 0, 0, 0)));
+        assert!(range.contains(IPv4Addr::new(0, 0, 0, 1)));
+        assert!(!range.contains(IPv4Addr::new(0, 0, 0, 2)));
+    }
+
+    #[test]
+    fn addr_range_set_from_str() {
+        assert_eq!(
+            AddrRangeSet::<u128>::from_str("1-10!2!5-6,8"),
+            Ok(AddrRangeSet {
+                list: AddrRangeList::from(AddrRange::new(1, 10)),
+                excludes: vec![
+                    AddrRangeList::from(AddrRange::new(2, 2)),
+                    AddrRangeList {
+                        ranges: vec![AddrRange::new(5, 6), AddrRange::new(8, 8)]
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn addr_range_set_from_str_err() {
+        assert_eq!(
+            AddrRangeSet::<u128>::from_str("1-10!"),
+            err(ParseErrorKind::Empty, 5)
+        );
+        assert_eq!(
+            AddrRangeSet::<u128>::from_str("!1"),
+            err(ParseErrorKind::Empty, 0)
+        );
+        assert_eq!(
+            AddrRangeSet::<u128>::from_str("1-10!!2"),
+            err(ParseErrorKind::Empty, 5)
+        );
+        assert_eq!(
+            AddrRangeSet::<u128>::from_str("1-10!x"),
+            err(ParseErrorKind::InvalidDigit, 5)
+        );
+    }
+
+    #[test]
+    fn addr_range_set_iter_ipv4() {
+        let set = AddrRangeSet::<IPv4Addr>::from_str(
+            "192.168.0.0/24!192.168.0.0-192.168.0.250!192.168.0.252!192.168.0.255",
+        )
+        .unwrap();
+        let mut iter = set.into_iter();
+        assert_eq!(iter.next(), Some(IPv4Addr::new(192, 168, 0, 251)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(192, 168, 0, 253)));
+        assert_eq!(iter.next(), Some(IPv4Addr::new(192, 168, 0, 254)));
+        assert_eq!(iter.next(), None);
+        iter.reset();
+        assert_eq!(iter.next(), Some(IPv4Addr::new(192, 168, 0, 251)));
+    }
+
+    #[test]
+    fn addr_range_set_iter_mac() {
+        let set = AddrRangeSet::<MacAddr>::from_str("0-5,10!1,3-10:2").unwrap();
+        assert_eq!(
+            set.into_iter().collect::<Vec<_>>(),
+            vec![
+                MacAddr::new(0, 0, 0, 0, 0, 0),
+                MacAddr::new(0, 0, 0, 0, 0, 2),
+                MacAddr::new(0, 0, 0, 0, 0, 4),
+                MacAddr::new(0, 0, 0, 0, 0, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn addr_range_set_iter_large_exclusion() {
+        let set = AddrRangeSet::<IPv6Addr>::from_str("::/0!::/1!8000::1-ffff::").unwrap();
+        let mut iter = set.into_iter();
+        assert_eq!(iter.next(), IPv6Addr::from_str("8000::").ok());
+        assert_eq!(iter.next(), IPv6Addr::from_str("ffff::1").ok());
+        let set =
+            AddrRangeSet::<IPv4Addr>::from_str("255.255.255.255-0.0.0.0:2!0.0.0.0-255.255.255.240")
+                .unwrap();
+        assert_eq!(
+            set.into_iter().collect::<Vec<_>>(),
+            (0..8)
+                .map(|i| IPv4Addr::new(255, 255, 255, 255 - 2 * i))
+                .collect::<Vec<_>>()
+        );
+        let set = AddrRangeSet::<u128>::from_str("0-0xffffffff!1-0xfffffffe").unwrap();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![0, 0xffffffff]);
+    }
+
+    #[test]
+    fn addr_range_set_iter_ipv6() {
+        let set =
+            AddrRangeSet::<IPv6Addr>::from_str("2001:db8::/126!2001:db8::1-2001:db8::2").unwrap();
+        assert_eq!(
+            set.into_iter().collect::<Vec<_>>(),
+            vec![
+                IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0),
+                IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn addr_range_set_iter_number_all_excluded() {
+        let set = AddrRangeSet::<u128>::from_str("1-3!0-10").unwrap();
+        assert_eq!(set.into_iter().next(), None);
+    }
+
     #[test]
     fn addr_range_ranges_iter_one_element() {
         let range = AddrRange::<IPv4Addr>::from_str("1-3").unwrap();
         let mut ranges = AddrRanges::<IPv4Addr>::new();
-        ranges.push(range.into_range());
+        ranges.push(AddrRangeSet::from(AddrRangeList::from(range.into_range())));
         let mut ranges_iter = ranges.into_iter();
         assert_eq!(ranges_iter.next(), Some(vec![IPv4Addr::new(0, 0, 0, 1)]));
         assert_eq!(ranges_iter.next(), Some(vec![IPv4Addr::new(0, 0, 0, 2)]));
@@ -340,12 +1541,31 @@ mod tests {
         assert_eq!(ranges_iter.next(), None);
     }
 
+    #[test]
+    fn addr_range_ranges_iter_hosts() {
+        let mut ranges = AddrRanges::<IPv4Addr>::new();
+        ranges.push(AddrRangeSet::<IPv4Addr>::from_str("10.0.0.0/30h").unwrap());
+        ranges.push(AddrRangeSet::<IPv4Addr>::from_str("10.0.1.0/29h").unwrap());
+        let ranges_iter = ranges.into_iter();
+        assert_eq!(
+            ranges_iter.collect::<Vec<_>>(),
+            vec![
+                vec![IPv4Addr::new(10, 0, 0, 1), IPv4Addr::new(10, 0, 1, 1)],
+                vec![IPv4Addr::new(10, 0, 0, 2), IPv4Addr::new(10, 0, 1, 2)],
+                vec![IPv4Addr::new(10, 0, 0, 1), IPv4Addr::new(10, 0, 1, 3)],
+                vec![IPv4Addr::new(10, 0, 0, 2), IPv4Addr::new(10, 0, 1, 4)],
+                vec![IPv4Addr::new(10, 0, 0, 1), IPv4Addr::new(10, 0, 1, 5)],
+                vec![IPv4Addr::new(10, 0, 0, 2), IPv4Addr::new(10, 0, 1, 6)],
+            ]
+        );
+    }
+
     #[test]
     fn addr_range_ranges_iter_3_elements() {
         let mut ranges = AddrRanges::<IPv4Addr>::new();
-        ranges.push(AddrRange::<IPv4Addr>::from_str("1-3").unwrap());
-        ranges.push(AddrRange::<IPv4Addr>::from_str("2-6").unwrap());
-        ranges.push(AddrRange::<IPv4Addr>::from_str("7-7").unwrap());
+        ranges.push(AddrRangeSet::<IPv4Addr>::from_str("1-3").unwrap());
+        ranges.push(AddrRangeSet::<IPv4Addr>::from_str("2-6").unwrap());
+        ranges.push(AddrRangeSet::<IPv4Addr>::from_str("7-7").unwrap());
         let mut ranges_iter = ranges.into_iter();
         assert_eq!(
             ranges_iter.next(),
@@ -389,4 +1609,43 @@ mod tests {
         );
         assert_eq!(ranges_iter.next(), None);
     }
+
+    #[test]
+    fn addr_range_new_with_step() {
+        let range = AddrRange::new(IPv4Addr::new(10, 0, 0, 9), IPv4Addr::new(10, 0, 0, 1));
+        assert_eq!(range.start(), &IPv4Addr::new(10, 0, 0, 9));
+        assert_eq!(range.end(), &IPv4Addr::new(10, 0, 0, 1));
+        assert_eq!(AddrRange::new(0u128, 9).with_step(0), None);
+        let range = range.with_step(4).unwrap();
+        assert_eq!(range.step(), 4);
+        assert_eq!(
+            Ok(&range),
+            AddrRange::from_str("10.0.0.9-10.0.0.1:4").as_ref()
+        );
+        assert!(range.contains(IPv4Addr::new(10, 0, 0, 5)));
+        assert!(!range.contains(IPv4Addr::new(10, 0, 0, 4)));
+        assert_eq!(
+            range.into_iter().collect::<Vec<_>>(),
+            vec![
+                IPv4Addr::new(10, 0, 0, 9),
+                IPv4Addr::new(10, 0, 0, 5),
+                IPv4Addr::new(10, 0, 0, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn addr_range_set_built() {
+        let mut list = AddrRangeList::from(AddrRange::new(1u128, 3));
+        list.push(AddrRange::new(7, 8));
+        let mut set = AddrRangeSet::from(list);
+        set.exclude(AddrRangeList::from(AddrRange::new(2, 2)));
+        assert_eq!(Ok(&set), AddrRangeSet::from_str("1-3,7-8!2").as_ref());
+        assert!(set.contains(1));
+        assert!(!set.contains(2));
+        assert!(!set.contains(5));
+        let cloned = set.clone();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![1, 3, 7, 8]);
+        assert_eq!(cloned.into_iter().collect::<Vec<_>>(), vec![1, 3, 7, 8]);
+    }
 }
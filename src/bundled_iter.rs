@@ -1,15 +1,66 @@
+use std::str::FromStr;
+
+/// Iterator which can be rewound to the first item.
 pub trait ResettableIterator: Iterator {
     fn reset(&mut self);
 }
 
+/// How `BundledIter` combines the bundled iterators.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+#[non_exhaustive]
+pub enum BundleMode {
+    /// Iterates until the longest iterator is done, shorter iterators are looped.
+    #[default]
+    Loop,
+    /// Iterates every combination of the iterators, the last iterator changes first.
+    Product,
+    /// Iterates until the shortest iterator is done.
+    Shortest,
+    /// Same as `Shortest`, but the iterators are expected to have the same length.
+    /// Iteration stops at the first row where some iterators are done and the others
+    /// are not, which `BundledIter::has_different_lengths` tells.
+    Strict,
+}
+
+impl FromStr for BundleMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "loop" => Ok(BundleMode::Loop),
+            "product" => Ok(BundleMode::Product),
+            "shortest" => Ok(BundleMode::Shortest),
+            "strict" => Ok(BundleMode::Strict),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Iterators to be iterated together, which yields a `Vec` of their items.
 pub struct IterBundle<I> {
     iters: Vec<I>,
+    mode: BundleMode,
 }
 
-pub struct BundledIter<I> {
+pub struct BundledIter<I>
+where
+    I: Iterator,
+{
     iters: Vec<I>,
+    mode: BundleMode,
     offset: i64,
     done: Vec<bool>,
+    current: Vec<I::Item>,
+    different_lengths: bool,
+}
+
+impl<I> Default for IterBundle<I>
+where
+    I: Iterator,
+{
+    fn default() -> Self {
+        IterBundle::new()
+    }
 }
 
 impl<I> IterBundle<I>
@@ -17,7 +68,22 @@ where
     I: Iterator,
 {
     pub fn new() -> Self {
-        IterBundle::<I> { iters: Vec::new() }
+        IterBundle::<I> {
+            iters: Vec::new(),
+            mode: BundleMode::default(),
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: BundleMode) {
+        self.mode = mode;
+    }
+
+    pub fn len(&self) -> usize {
+        self.iters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iters.is_empty()
     }
 
     pub fn push<T>(&mut self, iter: T)
@@ -31,6 +97,7 @@ where
 impl<I> IntoIterator for IterBundle<I>
 where
     I: ResettableIterator,
+    I::Item: Clone,
 {
     type Item = Vec<I::Item>;
     type IntoIter = BundledIter<I>;
@@ -39,13 +106,31 @@ where
         let done = self.iters.iter().map(|_| false).collect();
         BundledIter::<I> {
             iters: self.iters,
+            mode: self.mode,
             offset: 0,
-            done: done,
+            done,
+            current: vec![],
+            different_lengths: false,
         }
     }
 }
 
-impl<I> BundledIter<I> {
+impl<I> BundledIter<I>
+where
+    I: Iterator,
+{
+    /// Returns whether `Strict` mode stopped because some iterators were done
+    /// before the others.
+    pub fn has_different_lengths(&self) -> bool {
+        self.different_lengths
+    }
+}
+
+impl<I> BundledIter<I>
+where
+    I: ResettableIterator,
+    I::Item: Clone,
+{
     fn is_all_done(&self) -> bool {
         self.done.iter().all(|b| *b)
     }
@@ -57,15 +142,15 @@ impl<I> BundledIter<I> {
     fn len(&self) -> usize {
         self.done.len()
     }
-}
 
-impl<I> Iterator for BundledIter<I>
-where
-    I: ResettableIterator,
-{
-    type Item = Vec<I::Item>;
+    fn finish(&mut self) -> Option<Vec<I::Item>> {
+        for done in &mut self.done {
+            *done = true;
+        }
+        None
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    fn next_loop(&mut self) -> Option<Vec<I::Item>> {
         let mut count = self.count_done();
         if self.is_all_done() {
             None
@@ -80,11 +165,7 @@ where
                         }
                         self.done[v.len()] = true;
                         iter.reset();
-                        if let Some(next) = iter.next() {
-                            next
-                        } else {
-                            return None;
-                        }
+                        iter.next()?
                     }
                 };
                 v.push(next);
@@ -97,11 +178,90 @@ where
             }
         }
     }
+
+    fn next_shortest(&mut self) -> Option<Vec<I::Item>> {
+        if self.is_all_done() {
+            return None;
+        }
+        match self.iters.iter_mut().map(|iter| iter.next()).collect() {
+            Some(v) => {
+                self.offset += 1;
+                Some(v)
+            }
+            None => self.finish(),
+        }
+    }
+
+    fn next_strict(&mut self) -> Option<Vec<I::Item>> {
+        if self.is_all_done() {
+            return None;
+        }
+        // Every iterator is advanced to see whether they are done together.
+        let next: Vec<_> = self.iters.iter_mut().map(|iter| iter.next()).collect();
+        if next.iter().all(Option::is_some) {
+            self.offset += 1;
+            return next.into_iter().collect();
+        }
+        self.different_lengths = next.iter().any(Option::is_some);
+        self.finish()
+    }
+
+    fn next_product(&mut self) -> Option<Vec<I::Item>> {
+        if self.is_all_done() {
+            return None;
+        }
+        if self.current.is_empty() {
+            match self.iters.iter_mut().map(|iter| iter.next()).collect() {
+                Some(v) => self.current = v,
+                None => return self.finish(),
+            }
+        } else {
+            // Advance the last iterator which is not done yet, and restart the following ones.
+            let mut i = self.len();
+            loop {
+                if i == 0 {
+                    return self.finish();
+                }
+                i -= 1;
+                if let Some(next) = self.iters[i].next() {
+                    self.current[i] = next;
+                    break;
+                }
+            }
+            for j in i + 1..self.len() {
+                self.iters[j].reset();
+                match self.iters[j].next() {
+                    Some(next) => self.current[j] = next,
+                    None => return self.finish(),
+                }
+            }
+        }
+        self.offset += 1;
+        Some(self.current.clone())
+    }
+}
+
+impl<I> Iterator for BundledIter<I>
+where
+    I: ResettableIterator,
+    I::Item: Clone,
+{
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.mode {
+            BundleMode::Loop => self.next_loop(),
+            BundleMode::Product => self.next_product(),
+            BundleMode::Shortest => self.next_shortest(),
+            BundleMode::Strict => self.next_strict(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{IterBundle, ResettableIterator};
+    use super::{BundleMode, IterBundle, ResettableIterator};
+    use std::str::FromStr;
 
     struct VecIter {
         v: Vec<u32>,
@@ -194,4 +354,173 @@ mod tests {
         let mut iter = bundle.into_iter();
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn bundle_mode_from_str() {
+        assert_eq!(BundleMode::from_str("loop"), Ok(BundleMode::Loop));
+        assert_eq!(BundleMode::from_str("product"), Ok(BundleMode::Product));
+        assert_eq!(BundleMode::from_str("shortest"), Ok(BundleMode::Shortest));
+        assert_eq!(BundleMode::from_str("strict"), Ok(BundleMode::Strict));
+        assert_eq!(BundleMode::from_str("Loop"), Err(()));
+        assert_eq!(BundleMode::from_str(""), Err(()));
+    }
+
+    #[test]
+    fn bundled_iter_product_one_iter() {
+        let mut bundle = IterBundle::new();
+        bundle.set_mode(BundleMode::Product);
+        bundle.push(VecIter {
+            v: vec![1, 2, 3],
+            offset: 0,
+        });
+        let mut iter = bundle.into_iter();
+        assert_eq!(iter.next(), Some(vec![1]));
+        assert_eq!(iter.next(), Some(vec![2]));
+        assert_eq!(iter.next(), Some(vec![3]));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn bundled_iter_product_multiple_iters() {
+        let mut bundle = IterBundle::new();
+        bundle.set_mode(BundleMode::Product);
+        bundle.push(VecIter {
+            v: vec![1, 2],
+            offset: 0,
+        });
+        bundle.push(VecIter {
+            v: vec![5],
+            offset: 0,
+        });
+        bundle.push(VecIter {
+            v: vec![7, 8, 9],
+            offset: 0,
+        });
+        let mut iter = bundle.into_iter();
+        assert_eq!(iter.next(), Some(vec![1, 5, 7]));
+        assert_eq!(iter.next(), Some(vec![1, 5, 8]));
+        assert_eq!(iter.next(), Some(vec![1, 5, 9]));
+        assert_eq!(iter.next(), Some(vec![2, 5, 7]));
+        assert_eq!(iter.next(), Some(vec![2, 5, 8]));
+        assert_eq!(iter.next(), Some(vec![2, 5, 9]));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn bundled_iter_product_always_none() {
+        let mut bundle = IterBundle::new();
+        bundle.set_mode(BundleMode::Product);
+        bundle.push(VecIter {
+            v: vec![1, 2, 3],
+            offset: 0,
+        });
+        bundle.push(VecIter {
+            v: vec![],
+            offset: 0,
+        });
+        let mut iter = bundle.into_iter();
+        assert_eq!(iter.next(), None);
+
+        let mut bundle = IterBundle::<VecIter>::new();
+        bundle.set_mode(BundleMode::Product);
+        let mut iter = bundle.into_iter();
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn bundled_iter_shortest() {
+        let mut bundle = IterBundle::new();
+        bundle.set_mode(BundleMode::Shortest);
+        bundle.push(VecIter {
+            v: vec![1, 2, 3],
+            offset: 0,
+        });
+        bundle.push(VecIter {
+            v: vec![5, 6],
+            offset: 0,
+        });
+        bundle.push(VecIter {
+            v: vec![7, 8, 9, 10],
+            offset: 0,
+        });
+        let mut iter = bundle.into_iter();
+        assert_eq!(iter.next(), Some(vec![1, 5, 7]));
+        assert_eq!(iter.next(), Some(vec![2, 6, 8]));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn bundled_iter_shortest_always_none() {
+        let mut bundle = IterBundle::new();
+        bundle.set_mode(BundleMode::Shortest);
+        bundle.push(VecIter {
+            v: vec![1, 2, 3],
+            offset: 0,
+        });
+        bundle.push(VecIter {
+            v: vec![],
+            offset: 0,
+        });
+        let mut iter = bundle.into_iter();
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn bundled_iter_strict() {
+        let mut bundle = IterBundle::new();
+        bundle.set_mode(BundleMode::Strict);
+        bundle.push(VecIter {
+            v: vec![1, 2, 3],
+            offset: 0,
+        });
+        bundle.push(VecIter {
+            v: vec![4, 5, 6],
+            offset: 0,
+        });
+        let mut iter = bundle.into_iter();
+        assert_eq!(iter.next(), Some(vec![1, 4]));
+        assert_eq!(iter.next(), Some(vec![2, 5]));
+        assert_eq!(iter.next(), Some(vec![3, 6]));
+        assert_eq!(iter.next(), None);
+        assert!(!iter.has_different_lengths());
+    }
+
+    #[test]
+    fn bundled_iter_strict_different_lengths() {
+        let mut bundle = IterBundle::new();
+        bundle.set_mode(BundleMode::Strict);
+        bundle.push(VecIter {
+            v: vec![1, 2, 3],
+            offset: 0,
+        });
+        bundle.push(VecIter {
+            v: vec![4, 5],
+            offset: 0,
+        });
+        let mut iter = bundle.into_iter();
+        assert_eq!(iter.next(), Some(vec![1, 4]));
+        assert_eq!(iter.next(), Some(vec![2, 5]));
+        assert!(!iter.has_different_lengths());
+        assert_eq!(iter.next(), None);
+        assert!(iter.has_different_lengths());
+        assert_eq!(iter.next(), None);
+
+        let mut bundle = IterBundle::new();
+        bundle.set_mode(BundleMode::Strict);
+        bundle.push(VecIter {
+            v: vec![1],
+            offset: 0,
+        });
+        bundle.push(VecIter {
+            v: vec![4, 5],
+            offset: 0,
+        });
+        let mut iter = bundle.into_iter();
+        assert_eq!(iter.next(), Some(vec![1, 4]));
+        assert_eq!(iter.next(), None);
+        assert!(iter.has_different_lengths());
+    }
 }
@@ -1,30 +1,306 @@
 use std::error::Error;
 use std::fmt::{self, Display};
-use std::io::Write;
-use std::str::FromStr;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::str::{Chars, FromStr};
 
 use crate::addr::Addr;
-use crate::addr_range::{AddrRange, AddrRanges};
-use crate::ipv4addr::IPv4Addr;
-use crate::ipv6addr::{IPv6Addr, IPv6FullAddr};
-use crate::macaddr::MacAddr;
+use crate::addr_range::{AddrRangeSet, AddrRangeSetIter, AddrRanges};
+use crate::bundled_iter::{BundleMode, BundledIter};
+use crate::ipv4addr::{IPv4Addr, IPv4PtrAddr};
+use crate::ipv6addr::{IPv6Addr, IPv6CompressedAddr, IPv6FullAddr, IPv6MixedAddr, IPv6PtrAddr};
+use crate::macaddr::{MacAddr, MacNotation, MacNotationAddr, MAC_MAX};
+use crate::number::parse_number;
+use crate::parse_error::ParseError;
 
 #[derive(Debug, PartialEq)]
 pub enum Format {
     IPv4Addr,
-    IPv6Addr,
-    IPv6FullAddr,
-    MacAddr,
-    Number { zero_pad: bool, pad_len: u8 },
+    IPv6Addr {
+        upper: bool,
+    },
+    IPv6FullAddr {
+        upper: bool,
+    },
+    IPv6MixedAddr {
+        upper: bool,
+    },
+    IPv4EmbeddedAddr {
+        prefix: IPv6Addr,
+        upper: bool,
+        mixed: bool,
+    },
+    IPv4PtrAddr {
+        suffix: bool,
+    },
+    IPv6PtrAddr {
+        upper: bool,
+        suffix: bool,
+    },
+    SlaacAddr {
+        prefix: IPv6Addr,
+        upper: bool,
+    },
+    MacAddr {
+        notation: MacNotation,
+        upper: bool,
+    },
+    Number {
+        radix: Radix,
+        upper: bool,
+    },
+    Counter {
+        start: u128,
+        step: u128,
+    },
     RawString(String),
 }
 
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Radix {
+    Decimal,
+    Hex,
+    Octal,
+    Binary,
+}
+
+/// Type which an argument is parsed as.
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum ArgType {
+    IPv4,
+    IPv6,
+    Mac,
+    Number,
+}
+
+impl ArgType {
+    /// Maximum value of the argument.
+    fn max(self) -> u128 {
+        match self {
+            ArgType::IPv4 => u32::MAX.into(),
+            ArgType::Mac => MAC_MAX.into(),
+            ArgType::IPv6 | ArgType::Number => u128::MAX,
+        }
+    }
+}
+
 impl Format {
-    fn is_arg_required(&self) -> bool {
+    fn arg_type(&self) -> Option<ArgType> {
         match self {
-            Format::RawString(_) => false,
-            _ => true,
+            Format::IPv4Addr | Format::IPv4PtrAddr { .. } | Format::IPv4EmbeddedAddr { .. } => {
+                Some(ArgType::IPv4)
+            }
+            Format::IPv6Addr { .. }
+            | Format::IPv6FullAddr { .. }
+            | Format::IPv6MixedAddr { .. }
+            | Format::IPv6PtrAddr { .. } => Some(ArgType::IPv6),
+            Format::MacAddr { .. } | Format::SlaacAddr { .. } => Some(ArgType::Mac),
+            Format::Number { .. } => Some(ArgType::Number),
+            Format::Counter { .. } | Format::RawString(_) => None,
+        }
+    }
+}
+
+/// Format with the index of the argument it renders.
+#[derive(Debug, PartialEq)]
+pub struct Spec {
+    format: Format,
+    arg: Option<usize>,
+    expr: Expr,
+    part: Option<Part>,
+    padding: Padding,
+}
+
+impl Spec {
+    fn raw(s: String) -> Self {
+        Spec {
+            format: Format::RawString(s),
+            arg: None,
+            expr: Expr::default(),
+            part: None,
+            padding: Padding::default(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub enum Align {
+    Left,
+    #[default]
+    Right,
+    Center,
+}
+
+/// Width and alignment of a specifier, such as `-17` of `%-17m`.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct Padding {
+    /// Pads with `0` instead of space, unless aligned to left or center.
+    zero: bool,
+    align: Align,
+    width: u8,
+}
+
+impl Padding {
+    fn write<W>(&self, writer: &mut W, s: &str) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let len = (self.width as usize).saturating_sub(s.chars().count());
+        let (left, right) = match self.align {
+            Align::Left => (0, len),
+            Align::Right => (len, 0),
+            Align::Center => (len / 2, len - len / 2),
+        };
+        let fill = if self.zero && self.align == Align::Right {
+            "0"
+        } else {
+            " "
+        };
+        write!(writer, "{}{}{}", fill.repeat(left), s, fill.repeat(right))
+    }
+}
+
+/// Range of octets, bytes or hextets to print, such as `[0..3]` of `%m[0..3]`.
+#[derive(Debug, PartialEq)]
+pub struct Part {
+    start: usize,
+    end: usize,
+    radix: Option<Radix>,
+}
+
+impl Part {
+    /// Parses `[N]` or `[START..END]` following a specifier, which may end with
+    /// `d` or `x` to print in decimal or hexadecimal.
+    fn parse(chars: &mut FormatChars, len: usize) -> Result<Option<Self>, FormatError> {
+        if chars.peek() != Some('[') {
+            return Ok(None);
         }
+        chars.next();
+        let mut param = String::new();
+        loop {
+            match chars.next() {
+                Some(']') => break,
+                Some(c) => param.push(c),
+                None => return Err(FormatError::new(FormatErrorKind::UnterminatedIndex)),
+            }
+        }
+        let err = FormatError::new;
+        let (param, radix) = if let Some(param) = param.strip_suffix('d') {
+            (param, Some(Radix::Decimal))
+        } else if let Some(param) = param.strip_suffix('x') {
+            (param, Some(Radix::Hex))
+        } else {
+            (param.as_str(), None)
+        };
+        let parse_index = |s: &str| {
+            s.parse::<usize>()
+                .map_err(|_| err(FormatErrorKind::InvalidIndex))
+        };
+        let (start, end) = match param.split_once("..") {
+            Some((start, end)) => (parse_index(start)?, parse_index(end)?),
+            None => {
+                let start = parse_index(param)?;
+                let end = start
+                    .checked_add(1)
+                    .ok_or_else(|| err(FormatErrorKind::IndexOutOfRange))?;
+                (start, end)
+            }
+        };
+        if start >= end || end > len {
+            return Err(err(FormatErrorKind::IndexOutOfRange));
+        }
+        Ok(Some(Part { start, end, radix }))
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// Operations applied to the value of a specifier, such as `+1` of `%{i+1}`.
+/// `*` and `/` of constant terms and leading `*` are folded, so the operations are applied
+/// in order.
+#[derive(Debug, PartialEq, Default)]
+pub struct Expr {
+    ops: Vec<(Op, u128)>,
+}
+
+impl Expr {
+    fn parse(s: &str) -> Result<Self, FormatError> {
+        let err = FormatError::new;
+        let mut tokens = vec![];
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            let op = match c {
+                '+' => Op::Add,
+                '-' => Op::Sub,
+                '*' => Op::Mul,
+                '/' => Op::Div,
+                _ => return Err(err(FormatErrorKind::UnexpectedCharInExpression)),
+            };
+            let mut literal = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric()) {
+                literal.push(c);
+            }
+            let n = parse_number::<u128>(&literal)
+                .map_err(|_| err(FormatErrorKind::InvalidNumberInExpression))?;
+            if op == Op::Div && n == 0 {
+                return Err(err(FormatErrorKind::DivisionByZero));
+            }
+            tokens.push((op, n));
+        }
+        let out_of_range = || err(FormatErrorKind::NumberOutOfRangeInExpression);
+        let mut tokens = tokens.into_iter().peekable();
+        let mut ops: Vec<(Op, u128)> = vec![];
+        while let Some((op, n)) = tokens.next_if(|(op, _)| *op == Op::Mul || *op == Op::Div) {
+            match ops.last_mut() {
+                Some((Op::Mul, m)) if op == Op::Mul => {
+                    *m = m.checked_mul(n).ok_or_else(out_of_range)?;
+                }
+                _ => ops.push((op, n)),
+            }
+        }
+        while let Some((op, mut n)) = tokens.next() {
+            while let Some((op, m)) = tokens.next_if(|(op, _)| *op == Op::Mul || *op == Op::Div) {
+                n = if op == Op::Mul {
+                    n.checked_mul(m).ok_or_else(out_of_range)?
+                } else {
+                    n / m
+                };
+            }
+            ops.push((op, n));
+        }
+        Ok(Expr { ops })
+    }
+
+    fn is_additive(&self) -> bool {
+        self.ops
+            .iter()
+            .all(|(op, _)| *op == Op::Add || *op == Op::Sub)
+    }
+
+    fn apply_number(&self, value: u128) -> Result<u128, FormatError> {
+        self.ops.iter().try_fold(value, |value, (op, n)| {
+            match op {
+                Op::Add => value.checked_add(*n),
+                Op::Sub => value.checked_sub(*n),
+                Op::Mul => value.checked_mul(*n),
+                Op::Div => value.checked_div(*n),
+            }
+            .ok_or_else(|| FormatError::new(FormatErrorKind::ResultOutOfRange))
+        })
+    }
+
+    /// Only `+` and `-` are applied, which is ensured by `is_additive` on parsing.
+    fn apply(&self, addr: Addr) -> Addr {
+        self.ops.iter().fold(addr, |addr, (op, n)| match op {
+            Op::Sub => addr - *n,
+            _ => addr + *n,
+        })
     }
 }
 
@@ -32,10 +308,17 @@ impl Display for Format {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Format::IPv4Addr => write!(f, "IPv4 address"),
-            Format::IPv6Addr => write!(f, "IPv6 address"),
-            Format::IPv6FullAddr => write!(f, "IPv6 full address"),
-            Format::MacAddr => write!(f, "MAC address"),
+            Format::IPv6Addr { .. } => write!(f, "IPv6 address"),
+            Format::IPv6FullAddr { .. } => write!(f, "IPv6 full address"),
+            Format::IPv4PtrAddr { .. } | Format::IPv4EmbeddedAddr { .. } => {
+                write!(f, "IPv4 address")
+            }
+            Format::IPv6MixedAddr { .. } | Format::IPv6PtrAddr { .. } => {
+                write!(f, "IPv6 address")
+            }
+            Format::MacAddr { .. } | Format::SlaacAddr { .. } => write!(f, "MAC address"),
             Format::Number { .. } => write!(f, "Number"),
+            Format::Counter { .. } => write!(f, "Counter"),
             _ => write!(f, "Raw string"),
         }
     }
@@ -48,376 +331,2405 @@ enum FormatState {
     Escape,
 }
 
-#[derive(Debug, PartialEq)]
+/// What is wrong with a format string or its arguments.
+#[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
+pub enum FormatErrorKind {
+    UnexpectedChar,
+    UnexpectedCharAfterNotation,
+    InvalidUppercase,
+    InvalidSuffixOmission,
+    InvalidMixedNotation,
+    InvalidPosition,
+    PaddingOverflow,
+    UnterminatedParameter,
+    /// Invalid prefix parameter of the named specifier, such as `NAT64`.
+    InvalidPrefix(&'static str),
+    /// Missing prefix parameter of the named specifier, such as `SLAAC`.
+    MissingPrefix(&'static str),
+    InvalidZone,
+    InvalidCounterParameter,
+    InvalidExpression,
+    UnexpectedCharInExpression,
+    InvalidNumberInExpression,
+    /// Number in expression which does not fit in the address of the specifier.
+    NumberOutOfRangeInExpression,
+    /// Value of a number or row counter specifier which is negative or does not fit
+    /// in 128 bits.
+    ResultOutOfRange,
+    DivisionByZero,
+    InvalidMulDiv,
+    UnterminatedIndex,
+    InvalidIndex,
+    IndexOutOfRange,
+    UnknownEscape(char),
+    InvalidHexEscape,
+    HexEscapeOutOfRange,
+    InvalidUnicodeEscape,
+    UnicodeEscapeOutOfRange,
+    IncompleteEscape,
+    /// Specifiers of different address types refer to the argument of the (zero-based) index.
+    ConflictingSpecifiers(usize),
+    InsufficientArguments,
+    UnexpectedArgument,
+    /// Argument which cannot be parsed for the specifier.
+    InvalidArgument {
+        format: String,
+        error: ParseError,
+    },
+    DifferentLengths,
+    /// Address type which is not expected for the specifier.
+    UnexpectedAddr(String),
+    Io(String),
+}
+
+impl Display for FormatErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatErrorKind::UnexpectedChar => write!(f, "Unexpected character after %"),
+            FormatErrorKind::UnexpectedCharAfterNotation => {
+                write!(f, "Unexpected character after MAC address notation")
+            }
+            FormatErrorKind::InvalidUppercase => {
+                write!(f, "Uppercase is only for hexadecimal specifier")
+            }
+            FormatErrorKind::InvalidSuffixOmission => {
+                write!(f, "Suffix omission is only for PTR specifier")
+            }
+            FormatErrorKind::InvalidMixedNotation => {
+                write!(
+                    f,
+                    "Mixed notation is only for IPv6 and embedded IPv4 specifier"
+                )
+            }
+            FormatErrorKind::InvalidPosition => write!(f, "Invalid argument position"),
+            FormatErrorKind::PaddingOverflow => write!(f, "Padding length overflow"),
+            FormatErrorKind::UnterminatedParameter => write!(f, "Unterminated parameter"),
+            FormatErrorKind::InvalidPrefix(name) => write!(f, "Invalid {} prefix", name),
+            FormatErrorKind::MissingPrefix(name) => write!(f, "Missing {} prefix", name),
+            FormatErrorKind::InvalidZone => write!(f, "Invalid zone"),
+            FormatErrorKind::InvalidCounterParameter => write!(f, "Invalid counter parameter"),
+            FormatErrorKind::InvalidExpression => write!(f, "Invalid expression"),
+            FormatErrorKind::UnexpectedCharInExpression => {
+                write!(f, "Unexpected character in expression")
+            }
+            FormatErrorKind::InvalidNumberInExpression => {
+                write!(f, "Invalid number in expression")
+            }
+            FormatErrorKind::NumberOutOfRangeInExpression => {
+                write!(f, "Number out of range in expression")
+            }
+            FormatErrorKind::ResultOutOfRange => write!(f, "Result out of range"),
+            FormatErrorKind::DivisionByZero => write!(f, "Division by zero"),
+            FormatErrorKind::InvalidMulDiv => {
+                write!(f, "Multiplication and division are only for number")
+            }
+            FormatErrorKind::UnterminatedIndex => write!(f, "Unterminated index"),
+            FormatErrorKind::InvalidIndex => write!(f, "Invalid index"),
+            FormatErrorKind::IndexOutOfRange => write!(f, "Index out of range"),
+            FormatErrorKind::UnknownEscape(c) => write!(f, "Unknown escape sequence \\{}", c),
+            FormatErrorKind::InvalidHexEscape => {
+                write!(f, "\\x must be followed by 2 hexadecimal digits")
+            }
+            FormatErrorKind::HexEscapeOutOfRange => write!(f, "\\x is out of range 00 to 7F"),
+            FormatErrorKind::InvalidUnicodeEscape => {
+                write!(
+                    f,
+                    "\\u must be followed by 1 to 6 hexadecimal digits in braces"
+                )
+            }
+            FormatErrorKind::UnicodeEscapeOutOfRange => {
+                write!(f, "\\u is not a Unicode scalar value")
+            }
+            FormatErrorKind::IncompleteEscape => {
+                write!(f, "Incomplete escape sequence at end of format")
+            }
+            FormatErrorKind::ConflictingSpecifiers(i) => {
+                write!(f, "Conflicting specifiers for argument {}", i + 1)
+            }
+            FormatErrorKind::InsufficientArguments => {
+                write!(f, "Insufficient number of arguments")
+            }
+            FormatErrorKind::UnexpectedArgument => write!(f, "Unexpected argument"),
+            FormatErrorKind::InvalidArgument { format, error } => write!(
+                f,
+                "Invalid {} in argument {}: {}",
+                format,
+                error.arg().unwrap_or_default() + 1,
+                error
+            ),
+            FormatErrorKind::DifferentLengths => write!(f, "Ranges have different lengths"),
+            FormatErrorKind::UnexpectedAddr(expected) => write!(f, "{} expected", expected),
+            FormatErrorKind::Io(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct FormatError {
-    msg: String,
+    kind: FormatErrorKind,
+    span: Option<Range<usize>>,
+}
+
+impl FormatError {
+    fn new(kind: FormatErrorKind) -> Self {
+        FormatError { kind, span: None }
+    }
+
+    fn with_span(self, span: Range<usize>) -> Self {
+        FormatError {
+            span: Some(span),
+            ..self
+        }
+    }
+
+    pub fn kind(&self) -> &FormatErrorKind {
+        &self.kind
+    }
+
+    /// Byte range of the offending part in the format string, if the error is in it.
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+
+    /// Returns the error of parsing an argument, which knows the argument and the
+    /// position in it.
+    pub fn arg_error(&self) -> Option<&ParseError> {
+        match &self.kind {
+            FormatErrorKind::InvalidArgument { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<fmt::Error> for FormatError {
+    fn from(e: fmt::Error) -> Self {
+        FormatError::new(FormatErrorKind::Io(e.to_string()))
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(e: io::Error) -> Self {
+        FormatError::new(FormatErrorKind::Io(e.to_string()))
+    }
 }
 
 impl Display for FormatError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.msg)
+        write!(f, "{}", self.kind)
     }
 }
 
 impl Error for FormatError {}
 
-pub fn format_macipr<W>(
+pub fn format_macipr<W>(writer: &mut W, fmt_str: &str, args: &[String]) -> Result<(), FormatError>
+where
+    W: Write,
+{
+    format_macipr_with_mode(writer, fmt_str, args, BundleMode::default())
+}
+
+pub fn format_macipr_with_mode<W>(
     writer: &mut W,
     fmt_str: &str,
-    args: &Vec<String>,
+    args: &[String],
+    mode: BundleMode,
 ) -> Result<(), FormatError>
 where
     W: Write,
 {
-    let mut ranges = AddrRanges::<Addr>::new();
-    let mut offset = 0;
-    let fmts = parse_format(fmt_str)?;
-    for fmt in &fmts {
-        if fmt.is_arg_required() {
-            if let Some(s) = args.get(offset) {
-                let range = if *fmt == Format::IPv4Addr {
-                    AddrRange::<IPv4Addr>::from_str(s.as_ref()).map(|r| r.into_range())
-                } else if *fmt == Format::IPv6Addr || *fmt == Format::IPv6FullAddr {
-                    AddrRange::<IPv6Addr>::from_str(s.as_ref()).map(|r| r.into_range())
-                } else if *fmt == Format::MacAddr {
-                    AddrRange::<MacAddr>::from_str(s.as_ref()).map(|r| r.into_range())
-                } else {
-                    AddrRange::<u128>::from_str(s.as_ref()).map(|r| r.into_range())
-                }
-                .map_err(|_| FormatError {
-                    msg: format!("Invalid {}", fmt),
-                })?;
-                ranges.push(range);
-            } else {
-                return Err(FormatError {
-                    msg: "Insufficient number of arguments".to_string(),
-                });
-            }
-            offset += 1;
-        }
-    }
-    if offset != args.len() {
-        return Err(FormatError {
-            msg: "Unexpected argument".to_string(),
-        });
-    }
-    if offset == 0 {
-        if let Format::RawString(s) = &fmts[0] {
-            return Ok(write!(writer, "{}\n", s).map_err(|e| FormatError {
-                msg: format!("{}", e),
-            })?);
-        }
-    }
-    for v in ranges {
-        let mut iter = v.iter();
-        for fmt in &fmts {
-            match fmt {
-                Format::RawString(s) => write!(writer, "{}", s),
-                Format::IPv6FullAddr => {
-                    if let Addr::IPv6(value) = iter.next().unwrap() {
-                        write!(writer, "{}", IPv6FullAddr::wrap(*value))
-                    } else {
-                        return Err(FormatError {
-                            msg: "IPv6 expected".to_string(),
-                        });
-                    }
+    let mut template = Template::from_str(fmt_str)?;
+    template.set_mode(mode);
+    let ranges = template.parse_args(args)?;
+    template.write(writer, ranges)
+}
+
+/// Format string compiled once, which can be rendered against many sets of arguments.
+#[derive(Debug)]
+pub struct Template {
+    specs: Vec<Spec>,
+    /// Index of the specifier deciding how each argument is parsed.
+    arg_specs: Vec<Option<usize>>,
+    mode: BundleMode,
+}
+
+impl Template {
+    pub fn set_mode(&mut self, mode: BundleMode) {
+        self.mode = mode;
+    }
+
+    /// Number of arguments the format refers to.
+    pub fn arg_count(&self) -> usize {
+        self.arg_specs.len()
+    }
+
+    /// Parses each argument as the range of the addresses which the specifiers
+    /// referring to it expect.
+    pub fn parse_args<S>(&self, args: &[S]) -> Result<AddrRanges<Addr>, FormatError>
+    where
+        S: AsRef<str>,
+    {
+        self.check_arg_count(args.len())?;
+        let mut ranges = AddrRanges::<Addr>::new();
+        for (i, s) in args.iter().enumerate() {
+            let fmt = &self.specs[self.arg_specs[i].unwrap()].format;
+            let s = s.as_ref();
+            let range = match fmt.arg_type() {
+                Some(ArgType::IPv4) => {
+                    AddrRangeSet::<IPv4Addr>::from_str(s).map(|r| r.into_range())
                 }
-                Format::Number { zero_pad, pad_len } => {
-                    if *zero_pad {
-                        write!(writer, "{:01$}", iter.next().unwrap(), *pad_len as usize)
-                    } else {
-                        write!(writer, "{:1$}", iter.next().unwrap(), *pad_len as usize)
-                    }
+                Some(ArgType::IPv6) => {
+                    AddrRangeSet::<IPv6Addr>::from_str(s).map(|r| r.into_range())
                 }
-                _ => write!(writer, "{}", iter.next().unwrap()),
+                Some(ArgType::Mac) => AddrRangeSet::<MacAddr>::from_str(s).map(|r| r.into_range()),
+                _ => AddrRangeSet::<u128>::from_str(s).map(|r| r.into_range()),
             }
-            .map_err(|e| FormatError {
-                msg: format!("{}", e),
+            .map_err(|e| {
+                FormatError::new(FormatErrorKind::InvalidArgument {
+                    format: fmt.to_string(),
+                    error: e.with_arg(i),
+                })
             })?;
+            ranges.push(range);
         }
-        write!(writer, "\n").map_err(|e| FormatError {
-            msg: format!("{}", e),
-        })?;
+        Ok(ranges)
+    }
+
+    /// Returns the rows rendered lazily, each with the addresses it is rendered from.
+    /// In `Strict` mode, the rows are followed by an error if the ranges turn out to
+    /// have different lengths. Ranges may be typed ones converted by `into_range`, such as
+    /// `AddrRangeSet<IPv4Addr>`, in which case an address of a wrong type is
+    /// reported on rendering the row.
+    pub fn rows(&self, mut ranges: AddrRanges<Addr>) -> Result<Rows<'_>, FormatError> {
+        self.check_arg_count(ranges.len())?;
+        ranges.set_mode(self.mode);
+        // Without arguments, the format is rendered once.
+        let once = ranges.is_empty() && !self.specs.is_empty();
+        Ok(Rows {
+            specs: &self.specs,
+            rows: ranges.into_iter(),
+            once,
+            row: 0,
+            done: false,
+        })
+    }
+
+    /// Same as `rows`, but returns the rendered rows as strings without line terminators.
+    pub fn lines(&self, ranges: AddrRanges<Addr>) -> Result<Lines<'_>, FormatError> {
+        self.rows(ranges).map(Lines)
+    }
+
+    /// Writes the rendered rows, each followed by a newline.
+    pub fn write<W>(&self, writer: &mut W, ranges: AddrRanges<Addr>) -> Result<(), FormatError>
+    where
+        W: Write,
+    {
+        for line in self.lines(ranges)? {
+            writeln!(writer, "{}", line?)?;
+        }
+        Ok(())
+    }
+
+    /// Same as `write`, but renders to `fmt::Write` such as `String`.
+    pub fn render<W>(&self, writer: &mut W, ranges: AddrRanges<Addr>) -> Result<(), FormatError>
+    where
+        W: fmt::Write,
+    {
+        for line in self.lines(ranges)? {
+            writeln!(writer, "{}", line?)?;
+        }
+        Ok(())
+    }
+
+    fn check_arg_count(&self, count: usize) -> Result<(), FormatError> {
+        if count < self.arg_count() {
+            return Err(FormatError::new(FormatErrorKind::InsufficientArguments));
+        }
+        // An argument which no specifier refers to is unexpected as well as extra ones.
+        if count > self.arg_count() || self.arg_specs.contains(&None) {
+            return Err(FormatError::new(FormatErrorKind::UnexpectedArgument));
+        }
+        Ok(())
     }
-    Ok(())
 }
 
-fn parse_format(fmt_str: &str) -> Result<Vec<Format>, FormatError> {
-    let mut fmts = vec![];
-    let mut buf = String::new();
-    let mut state = FormatState::Normal;
-    let mut zero_pad = false;
-    let mut pad_len = 0;
-    for c in fmt_str.chars() {
-        if state == FormatState::Percent {
-            state = FormatState::Normal;
-            if c == '%' {
-                buf.push('%');
-                zero_pad = false;
-                pad_len = 0;
-            } else {
-                if !buf.is_empty() {
-                    fmts.push(Format::RawString(buf));
-                    buf = String::new();
+impl FromStr for Template {
+    type Err = FormatError;
+
+    fn from_str(fmt_str: &str) -> Result<Self, Self::Err> {
+        let parsed = parse_format(fmt_str)?;
+        let mut errors = vec![];
+        let arg_specs = arg_specs(&parsed, &mut errors);
+        if !errors.is_empty() {
+            return Err(errors.remove(0));
+        }
+        Ok(Template {
+            specs: parsed.specs,
+            arg_specs,
+            mode: BundleMode::default(),
+        })
+    }
+}
+
+/// Row rendered by `Template::rows`, which consists of the address of each argument
+/// and the rendered text of each specifier including raw strings.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Row {
+    addrs: Vec<Addr>,
+    pieces: Vec<String>,
+}
+
+impl Row {
+    /// Addresses of the arguments in this row, before applying expressions.
+    pub fn addrs(&self) -> &[Addr] {
+        &self.addrs
+    }
+
+    pub fn pieces(&self) -> &[String] {
+        &self.pieces
+    }
+
+    pub fn into_parts(self) -> (Vec<Addr>, Vec<String>) {
+        (self.addrs, self.pieces)
+    }
+}
+
+/// Concatenates the pieces, which is the row printed without the line terminator.
+impl Display for Row {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.pieces.iter().try_for_each(|s| f.write_str(s))
+    }
+}
+
+/// Iterator over the rows rendered by `Template::rows`.
+pub struct Rows<'a> {
+    specs: &'a [Spec],
+    rows: BundledIter<AddrRangeSetIter<Addr>>,
+    once: bool,
+    row: usize,
+    done: bool,
+}
+
+impl Iterator for Rows<'_> {
+    type Item = Result<Row, FormatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let addrs = if self.once {
+            self.once = false;
+            vec![]
+        } else if let Some(addrs) = self.rows.next() {
+            addrs
+        } else {
+            self.done = true;
+            if self.rows.has_different_lengths() {
+                return Some(Err(FormatError::new(FormatErrorKind::DifferentLengths)));
+            }
+            return None;
+        };
+        let pieces = render_row(self.specs, &addrs, self.row);
+        self.row += 1;
+        Some(pieces.map(|pieces| Row { addrs, pieces }))
+    }
+}
+
+/// Iterator over the rows rendered by `Template::lines`.
+pub struct Lines<'a>(Rows<'a>);
+
+impl Iterator for Lines<'_> {
+    type Item = Result<String, FormatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|row| row.map(|row| row.to_string()))
+    }
+}
+
+/// Returns the index of the specifier deciding how each argument is parsed, reporting
+/// conflicts to `errors`. Number specifiers can render any argument, so the first
+/// other specifier referring to an argument decides it.
+fn arg_specs(parsed: &ParsedSpecs, errors: &mut Vec<FormatError>) -> Vec<Option<usize>> {
+    let specs = &parsed.specs;
+    let mut arg_specs: Vec<Option<usize>> = vec![];
+    for (j, spec) in specs.iter().enumerate() {
+        if let Some(i) = spec.arg {
+            if arg_specs.len() <= i {
+                arg_specs.resize(i + 1, None);
+            }
+            match arg_specs[i].map(|k| &specs[k].format) {
+                Some(fmt) if fmt.arg_type() == Some(ArgType::Number) => arg_specs[i] = Some(j),
+                Some(fmt)
+                    if spec.format.arg_type() != Some(ArgType::Number)
+                        && spec.format.arg_type() != fmt.arg_type() =>
+                {
+                    errors.push(
+                        FormatError::new(FormatErrorKind::ConflictingSpecifiers(i))
+                            .with_span(parsed.spans[j].clone()),
+                    );
                 }
-                match c {
-                    'i' => fmts.push(Format::IPv4Addr),
-                    'x' => fmts.push(Format::IPv6Addr),
-                    'X' => fmts.push(Format::IPv6FullAddr),
-                    'm' => fmts.push(Format::MacAddr),
-                    'n' => fmts.push(Format::Number { zero_pad, pad_len }),
-                    '0'..='9' => {
-                        state = FormatState::Percent;
-                        if c == '0' && pad_len == 0 {
-                            zero_pad = true;
-                        }
-                        pad_len = pad_len
-                            .checked_mul(10)
-                            .and_then(|n| n.checked_add(c.to_digit(10).unwrap_or(0) as u8))
-                            .ok_or(FormatError {
-                                msg: "Padding length overflow".to_string(),
-                            })?;
-                    }
-                    _ => {
-                        return Err(FormatError {
-                            msg: "Unexpected character after %".to_string(),
-                        });
-                    }
+                Some(_) => {}
+                None => arg_specs[i] = Some(j),
+            }
+        }
+    }
+    arg_specs
+}
+
+/// Renders each specifier of the row.
+fn render_row(specs: &[Spec], v: &[Addr], row: usize) -> Result<Vec<String>, FormatError> {
+    specs
+        .iter()
+        .map(|spec| {
+            let addr = match spec.arg {
+                Some(i) if spec.format.arg_type() == Some(ArgType::Number) => {
+                    Some(Addr::Number(spec.expr.apply_number(u128::from(v[i]))?))
                 }
+                Some(i) => Some(spec.expr.apply(v[i])),
+                None => None,
+            };
+            let mut buf = vec![];
+            write_spec(&mut buf, spec, addr, row)?;
+            if spec.padding.width > 0 {
+                let s = String::from_utf8_lossy(&buf).into_owned();
+                buf.clear();
+                spec.padding.write(&mut buf, &s)?;
             }
-        } else if state == FormatState::Escape {
-            state = FormatState::Normal;
-            match c {
-                '\\' => buf.push('\\'),
-                'n' => buf.push('\n'),
-                _ => {
-                    return Err(FormatError {
-                        msg: "Unexpected character after \\".to_string(),
-                    })
+            Ok(String::from_utf8_lossy(&buf).into_owned())
+        })
+        .collect()
+}
+
+fn write_spec<W>(
+    writer: &mut W,
+    spec: &Spec,
+    addr: Option<Addr>,
+    row: usize,
+) -> Result<(), FormatError>
+where
+    W: Write,
+{
+    if let (Some(part), Some(addr)) = (&spec.part, addr) {
+        return write_part(writer, &spec.format, addr, part);
+    }
+    match &spec.format {
+        Format::RawString(s) => write!(writer, "{}", s),
+        Format::IPv4Addr => {
+            if let Addr::IPv4(value) = addr.unwrap() {
+                write!(writer, "{}", value)
+            } else {
+                return Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                    "IPv4".to_string(),
+                )));
+            }
+        }
+        Format::IPv6Addr { upper } => {
+            if let Addr::IPv6(value) = addr.unwrap() {
+                write!(writer, "{}", IPv6CompressedAddr::wrap(value, *upper))
+            } else {
+                return Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                    "IPv6".to_string(),
+                )));
+            }
+        }
+        Format::IPv6FullAddr { upper } => {
+            if let Addr::IPv6(value) = addr.unwrap() {
+                write!(writer, "{}", IPv6FullAddr::wrap(value, *upper))
+            } else {
+                return Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                    "IPv6".to_string(),
+                )));
+            }
+        }
+        Format::IPv6MixedAddr { upper } => {
+            if let Addr::IPv6(value) = addr.unwrap() {
+                write!(writer, "{}", IPv6MixedAddr::wrap(value, *upper))
+            } else {
+                return Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                    "IPv6".to_string(),
+                )));
+            }
+        }
+        Format::IPv4EmbeddedAddr {
+            prefix,
+            upper,
+            mixed,
+        } => {
+            if let Addr::IPv4(value) = addr.unwrap() {
+                let addr = prefix.with_ipv4(u32::from(value));
+                if *mixed {
+                    write!(writer, "{}", IPv6MixedAddr::wrap(addr, *upper))
+                } else {
+                    write!(writer, "{}", IPv6CompressedAddr::wrap(addr, *upper))
                 }
+            } else {
+                return Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                    "IPv4".to_string(),
+                )));
             }
-        } else {
-            if c == '%' {
-                state = FormatState::Percent;
-                continue;
-            } else if c == '\\' {
-                state = FormatState::Escape;
-                continue;
+        }
+        Format::IPv4PtrAddr { suffix } => {
+            if let Addr::IPv4(value) = addr.unwrap() {
+                write!(writer, "{}", IPv4PtrAddr::wrap(value, *suffix))
+            } else {
+                return Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                    "IPv4".to_string(),
+                )));
             }
-            buf.push(c);
+        }
+        Format::IPv6PtrAddr { upper, suffix } => {
+            if let Addr::IPv6(value) = addr.unwrap() {
+                write!(writer, "{}", IPv6PtrAddr::wrap(value, *suffix, *upper))
+            } else {
+                return Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                    "IPv6".to_string(),
+                )));
+            }
+        }
+        Format::SlaacAddr { prefix, upper } => {
+            if let Addr::Mac(value) = addr.unwrap() {
+                let addr = prefix.with_interface_id(value.modified_eui64());
+                write!(writer, "{}", IPv6CompressedAddr::wrap(addr, *upper))
+            } else {
+                return Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                    "MAC".to_string(),
+                )));
+            }
+        }
+        Format::MacAddr { notation, upper } => {
+            if let Addr::Mac(value) = addr.unwrap() {
+                write!(
+                    writer,
+                    "{}",
+                    MacNotationAddr::wrap(value, *notation, *upper)
+                )
+            } else {
+                return Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                    "MAC".to_string(),
+                )));
+            }
+        }
+        Format::Number { radix, upper } => {
+            write_number(writer, u128::from(addr.unwrap()), *radix, *upper)
+        }
+        Format::Counter { start, step } => {
+            let value = step
+                .checked_mul(row as u128)
+                .and_then(|n| n.checked_add(*start))
+                .ok_or_else(|| FormatError::new(FormatErrorKind::ResultOutOfRange))?;
+            write_number(
+                writer,
+                spec.expr.apply_number(value)?,
+                Radix::Decimal,
+                false,
+            )
         }
     }
-    if !buf.is_empty() {
-        fmts.push(Format::RawString(buf));
+    .map_err(FormatError::from)
+}
+
+fn write_part<W>(
+    writer: &mut W,
+    format: &Format,
+    addr: Addr,
+    part: &Part,
+) -> Result<(), FormatError>
+where
+    W: Write,
+{
+    // Values, separator, radix, width in hexadecimal and whether to use uppercase.
+    let (values, sep, radix, width, upper): (Vec<u16>, _, _, _, _) = match (format, addr) {
+        (Format::IPv4Addr, Addr::IPv4(value)) => (
+            value.octets().iter().map(|&n| n.into()).collect(),
+            ".",
+            Radix::Decimal,
+            2,
+            false,
+        ),
+        (Format::IPv6Addr { upper }, Addr::IPv6(value)) => {
+            (value.segments().to_vec(), ":", Radix::Hex, 0, *upper)
+        }
+        (Format::IPv6FullAddr { upper }, Addr::IPv6(value)) => {
+            (value.segments().to_vec(), ":", Radix::Hex, 4, *upper)
+        }
+        (Format::MacAddr { notation, upper }, Addr::Mac(value)) => (
+            value.bytes().iter().map(|&n| n.into()).collect(),
+            match notation {
+                MacNotation::Colon => ":",
+                MacNotation::Hyphen => "-",
+                _ => "",
+            },
+            Radix::Hex,
+            2,
+            *upper,
+        ),
+        _ => {
+            return Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                format.to_string(),
+            )))
+        }
+    };
+    for (i, n) in values[part.start..part.end].iter().enumerate() {
+        if i > 0 {
+            write!(writer, "{}", sep)
+        } else {
+            Ok(())
+        }
+        .and_then(|_| match part.radix.unwrap_or(radix) {
+            Radix::Hex if upper => write!(writer, "{:01$X}", n, width),
+            Radix::Hex => write!(writer, "{:01$x}", n, width),
+            _ => write!(writer, "{}", n),
+        })?;
     }
-    Ok(fmts)
+    Ok(())
 }
 
-#[cfg(test)]
+fn write_number<W>(writer: &mut W, value: u128, radix: Radix, upper: bool) -> io::Result<()>
+where
+    W: Write,
+{
+    match radix {
+        Radix::Decimal => write!(writer, "{}", value),
+        Radix::Hex if upper => write!(writer, "{:X}", value),
+        Radix::Hex => write!(writer, "{:x}", value),
+        Radix::Octal => write!(writer, "{:o}", value),
+        Radix::Binary => write!(writer, "{:b}", value),
+    }
+}
+
+/// Reads `{...}` parameter following a specifier character, if any.
+fn parse_param(chars: &mut FormatChars) -> Result<Option<String>, FormatError> {
+    if chars.peek() != Some('{') {
+        return Ok(None);
+    }
+    chars.next();
+    parse_braced(chars).map(Some)
+}
+
+/// Parses `SPEC OPS` of `%{SPEC OPS}`, such as `i+1` and `05n*2`.
+fn parse_expr_spec(s: &str) -> Result<Spec, FormatError> {
+    let err = || FormatError::new(FormatErrorKind::InvalidExpression);
+    let i = s.find(|c: char| c.is_ascii_alphabetic()).ok_or_else(err)?;
+    let (spec, ops) = s.split_at(i + 1);
+    // Position must be placed outside of braces.
+    if spec.contains('$') {
+        return Err(err());
+    }
+    let mut specs = parse_format(&format!("%{}", spec))?.specs;
+    let mut spec = match specs.pop() {
+        Some(spec) if specs.is_empty() && spec.format != Format::RawString(String::new()) => spec,
+        _ => return Err(err()),
+    };
+    spec.expr = Expr::parse(ops)?;
+    if !spec.expr.is_additive()
+        && !matches!(spec.format, Format::Number { .. } | Format::Counter { .. })
+    {
+        return Err(FormatError::new(FormatErrorKind::InvalidMulDiv));
+    }
+    // Addresses would silently drop the bits above their width.
+    let max = spec.format.arg_type().map_or(u128::MAX, ArgType::max);
+    if spec.expr.ops.iter().any(|&(_, n)| n > max) {
+        return Err(FormatError::new(
+            FormatErrorKind::NumberOutOfRangeInExpression,
+        ));
+    }
+    Ok(spec)
+}
+
+/// Reads characters up to `}`.
+fn parse_braced(chars: &mut FormatChars) -> Result<String, FormatError> {
+    let mut param = String::new();
+    for c in chars {
+        if c == '}' {
+            return Ok(param);
+        }
+        param.push(c);
+    }
+    Err(FormatError::new(FormatErrorKind::UnterminatedParameter))
+}
+
+/// Parses IPv6 prefix parameter, which may have `/prefix_len` suffix but must not have host bits.
+fn parse_prefix(
+    param: String,
+    prefix_len: u8,
+    name: &'static str,
+) -> Result<IPv6Addr, FormatError> {
+    let err = || FormatError::new(FormatErrorKind::InvalidPrefix(name));
+    let suffix = format!("/{}", prefix_len);
+    let prefix =
+        IPv6Addr::from_str(param.strip_suffix(&suffix).unwrap_or(&param)).map_err(|_| err())?;
+    if u128::from(prefix) & (u128::MAX >> prefix_len) != 0 {
+        return Err(err());
+    }
+    Ok(prefix)
+}
+
+/// Parses `%e` with NAT64 prefix parameter or `%f`, which is always IPv4-mapped.
+fn parse_ipv4_embedded(
+    c: char,
+    chars: &mut FormatChars,
+    upper: bool,
+    mixed: bool,
+) -> Result<Format, FormatError> {
+    let prefix = if c == 'e' {
+        let param = parse_param(chars)?.unwrap_or_else(|| "64:ff9b::".to_string());
+        parse_prefix(param, 96, "NAT64")?
+    } else {
+        IPv6Addr::from(0xffff_0000_0000u128)
+    };
+    Ok(Format::IPv4EmbeddedAddr {
+        prefix,
+        upper,
+        mixed,
+    })
+}
+
+/// Parses `START` or `START:STEP` parameter of counter specifier.
+fn parse_counter_param(param: Option<String>) -> Result<(u128, u128), FormatError> {
+    let param = match param {
+        Some(param) => param,
+        None => return Ok((1, 1)),
+    };
+    let err = || FormatError::new(FormatErrorKind::InvalidCounterParameter);
+    let (start, step) = match param.split_once(':') {
+        Some((start, step)) => (start, step),
+        None => (param.as_str(), "1"),
+    };
+    Ok((
+        parse_number(start).map_err(|_| err())?,
+        parse_number(step).map_err(|_| err())?,
+    ))
+}
+
+fn parse_link_local_prefix(param: Option<String>) -> Result<IPv6Addr, FormatError> {
+    let prefix = match param {
+        Some(zone) => format!("fe80::%{}", zone),
+        None => "fe80::".to_string(),
+    };
+    IPv6Addr::from_str(&prefix).map_err(|_| FormatError::new(FormatErrorKind::InvalidZone))
+}
+
+/// Characters of format string, which know their byte offset for error reporting.
+struct FormatChars<'a> {
+    fmt_str: &'a str,
+    chars: Chars<'a>,
+}
+
+impl<'a> FormatChars<'a> {
+    fn new(fmt_str: &'a str) -> Self {
+        FormatChars {
+            fmt_str,
+            chars: fmt_str.chars(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.clone().next()
+    }
+
+    fn offset(&self) -> usize {
+        self.fmt_str.len() - self.chars.as_str().len()
+    }
+}
+
+impl Iterator for FormatChars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chars.next()
+    }
+}
+
+/// Returns every problem in the format string, not only the first one.
+/// Parsing resumes after each invalid specifier or escape sequence, which is skipped as if
+/// it took no argument, and then the specifiers parsed so far are checked for conflicts.
+pub fn check_format(fmt_str: &str) -> Vec<FormatError> {
+    let mut errors = vec![];
+    let mut chars = FormatChars::new(fmt_str);
+    let mut parsed = ParsedSpecs::default();
+    while let Err(e) = parse_specs(&mut chars, &mut parsed) {
+        errors.push(e.with_span(parsed.spec_start..chars.offset()));
+        parsed.raw_start = chars.offset();
+    }
+    arg_specs(&parsed, &mut errors);
+    errors.sort_by_key(|e| e.span().map(|span| span.start));
+    errors
+}
+
+fn parse_format(fmt_str: &str) -> Result<ParsedSpecs, FormatError> {
+    let mut chars = FormatChars::new(fmt_str);
+    let mut parsed = ParsedSpecs::default();
+    parse_specs(&mut chars, &mut parsed)
+        .map_err(|e| e.with_span(parsed.spec_start..chars.offset()))?;
+    Ok(parsed)
+}
+
+/// Specifiers parsed so far with their spans in the format string.
+#[derive(Debug, PartialEq, Default)]
+struct ParsedSpecs {
+    specs: Vec<Spec>,
+    spans: Vec<Range<usize>>,
+    next_arg: usize,
+    /// Offset of the last `%` or `\\`, so that an error can be located from there up to
+    /// the current offset.
+    spec_start: usize,
+    /// Offset where the raw string being read starts.
+    raw_start: usize,
+}
+
+impl ParsedSpecs {
+    fn push(&mut self, spec: Spec, span: Range<usize>) {
+        self.raw_start = span.end;
+        self.specs.push(spec);
+        self.spans.push(span);
+    }
+}
+
+/// Parses specifiers into `parsed` until the end of the format string or an error.
+fn parse_specs(chars: &mut FormatChars, parsed: &mut ParsedSpecs) -> Result<(), FormatError> {
+    let mut position = None;
+    let mut buf = String::new();
+    let mut state = FormatState::Normal;
+    let mut padding = Padding::default();
+    let mut notation = None;
+    let mut mixed = false;
+    let mut upper = false;
+    let mut suffix = true;
+    while let Some(c) = chars.next() {
+        if state == FormatState::Percent {
+            state = FormatState::Normal;
+            if c == '%' {
+                buf.push('%');
+            } else {
+                if !buf.is_empty() {
+                    parsed.push(Spec::raw(buf), parsed.raw_start..parsed.spec_start);
+                    buf = String::new();
+                }
+                let mut expr = Expr::default();
+                let format = match c {
+                    '{' if !upper && notation.is_none() && !mixed && suffix => {
+                        let inner = parse_expr_spec(&parse_braced(chars)?)?;
+                        expr = inner.expr;
+                        // Padding can be placed either outside or inside of braces.
+                        if padding == Padding::default() {
+                            padding = inner.padding;
+                        }
+                        inner.format
+                    }
+                    'm' if !mixed => Format::MacAddr {
+                        notation: notation.unwrap_or(MacNotation::Colon),
+                        upper,
+                    },
+                    _ if notation.is_some() => {
+                        return Err(FormatError::new(
+                            FormatErrorKind::UnexpectedCharAfterNotation,
+                        ));
+                    }
+                    'x' if mixed => Format::IPv6MixedAddr { upper },
+                    'e' | 'f' if mixed => parse_ipv4_embedded(c, chars, upper, true)?,
+                    _ if mixed => {
+                        return Err(FormatError::new(FormatErrorKind::InvalidMixedNotation));
+                    }
+                    '^' => {
+                        state = FormatState::Percent;
+                        upper = true;
+                        continue;
+                    }
+                    'i' | 'n' | 'o' | 'b' | 'r' | 'c' if upper => {
+                        return Err(FormatError::new(FormatErrorKind::InvalidUppercase));
+                    }
+                    '~' => {
+                        state = FormatState::Percent;
+                        suffix = false;
+                        continue;
+                    }
+                    'r' => Format::IPv4PtrAddr { suffix },
+                    'R' => Format::IPv6PtrAddr { upper, suffix },
+                    _ if !suffix => {
+                        return Err(FormatError::new(FormatErrorKind::InvalidSuffixOmission));
+                    }
+                    // '-' followed by width is the left alignment, otherwise the hyphen notation.
+                    '<' | '>' | '=' | '-'
+                        if c != '-' || matches!(chars.peek(), Some('0'..='9')) =>
+                    {
+                        state = FormatState::Percent;
+                        padding.align = match c {
+                            '>' => Align::Right,
+                            '=' => Align::Center,
+                            _ => Align::Left,
+                        };
+                        continue;
+                    }
+                    ',' => {
+                        state = FormatState::Percent;
+                        mixed = true;
+                        continue;
+                    }
+                    ':' | '-' | '.' | '_' | '#' => {
+                        state = FormatState::Percent;
+                        notation = Some(match c {
+                            ':' => MacNotation::Colon,
+                            '-' => MacNotation::Hyphen,
+                            '.' => MacNotation::Dot,
+                            '_' => MacNotation::Bare,
+                            _ => MacNotation::Hex,
+                        });
+                        continue;
+                    }
+                    'i' => Format::IPv4Addr,
+                    'x' => Format::IPv6Addr { upper },
+                    'X' => Format::IPv6FullAddr { upper },
+                    'e' | 'f' => parse_ipv4_embedded(c, chars, upper, false)?,
+                    'l' => Format::SlaacAddr {
+                        prefix: parse_link_local_prefix(parse_param(chars)?)?,
+                        upper,
+                    },
+                    's' => {
+                        let param = parse_param(chars)?.ok_or_else(|| {
+                            FormatError::new(FormatErrorKind::MissingPrefix("SLAAC"))
+                        })?;
+                        Format::SlaacAddr {
+                            prefix: parse_prefix(param, 64, "SLAAC")?,
+                            upper,
+                        }
+                    }
+                    'c' => {
+                        let (start, step) = parse_counter_param(parse_param(chars)?)?;
+                        Format::Counter { start, step }
+                    }
+                    'n' | 'h' | 'H' | 'o' | 'b' => Format::Number {
+                        radix: match c {
+                            'n' => Radix::Decimal,
+                            'h' | 'H' => Radix::Hex,
+                            'o' => Radix::Octal,
+                            _ => Radix::Binary,
+                        },
+                        upper: upper || c == 'H',
+                    },
+                    '$' if padding.width > 0 && position.is_none() => {
+                        state = FormatState::Percent;
+                        position = Some(padding.width as usize - 1);
+                        padding = Padding::default();
+                        continue;
+                    }
+                    '$' => {
+                        return Err(FormatError::new(FormatErrorKind::InvalidPosition));
+                    }
+                    '0'..='9' => {
+                        state = FormatState::Percent;
+                        if c == '0' && padding.width == 0 {
+                            padding.zero = true;
+                        }
+                        padding.width = padding
+                            .width
+                            .checked_mul(10)
+                            .and_then(|n| n.checked_add(c.to_digit(10).unwrap_or(0) as u8))
+                            .ok_or(FormatError::new(FormatErrorKind::PaddingOverflow))?;
+                        continue;
+                    }
+                    _ => {
+                        return Err(FormatError::new(FormatErrorKind::UnexpectedChar));
+                    }
+                };
+                // Positional arguments don't advance sequential arguments.
+                let arg = match (format.arg_type(), position) {
+                    (Some(_), Some(position)) => Some(position),
+                    (Some(_), None) => {
+                        parsed.next_arg += 1;
+                        Some(parsed.next_arg - 1)
+                    }
+                    (None, None) => None,
+                    (None, Some(_)) => {
+                        return Err(FormatError::new(FormatErrorKind::InvalidPosition));
+                    }
+                };
+                let part = match format {
+                    Format::IPv4Addr => Part::parse(chars, 4)?,
+                    Format::MacAddr { .. } => Part::parse(chars, 6)?,
+                    Format::IPv6Addr { .. } | Format::IPv6FullAddr { .. } => Part::parse(chars, 8)?,
+                    _ => None,
+                };
+                let spec = Spec {
+                    format,
+                    arg,
+                    expr,
+                    part,
+                    padding,
+                };
+                parsed.push(spec, parsed.spec_start..chars.offset());
+            }
+        } else if state == FormatState::Escape {
+            state = FormatState::Normal;
+            buf.push(parse_escape(c, chars)?);
+        } else {
+            if c == '%' {
+                parsed.spec_start = chars.offset() - 1;
+                state = FormatState::Percent;
+                padding = Padding::default();
+                notation = None;
+                mixed = false;
+                upper = false;
+                suffix = true;
+                position = None;
+                continue;
+            } else if c == '\\' {
+                parsed.spec_start = chars.offset() - 1;
+                state = FormatState::Escape;
+                continue;
+            }
+            buf.push(c);
+        }
+    }
+    if state == FormatState::Escape {
+        return Err(FormatError::new(FormatErrorKind::IncompleteEscape));
+    }
+    if !buf.is_empty() {
+        parsed.push(Spec::raw(buf), parsed.raw_start..chars.offset());
+    }
+    Ok(())
+}
+
+/// Translates escape sequence following `\\`.
+fn parse_escape(c: char, chars: &mut FormatChars) -> Result<char, FormatError> {
+    let err = |kind| Err(FormatError::new(kind));
+    let hex = |digits: &str| {
+        if digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            u32::from_str_radix(digits, 16).ok()
+        } else {
+            None
+        }
+    };
+    match c {
+        '\\' => Ok('\\'),
+        'n' => Ok('\n'),
+        't' => Ok('\t'),
+        'r' => Ok('\r'),
+        '0' => Ok('\0'),
+        'e' => Ok('\x1b'),
+        'x' => {
+            let digits: String = chars.take(2).collect();
+            match hex(&digits) {
+                Some(n) if digits.len() == 2 && n <= 0x7f => Ok(char::from(n as u8)),
+                Some(_) if digits.len() == 2 => err(FormatErrorKind::HexEscapeOutOfRange),
+                _ => err(FormatErrorKind::InvalidHexEscape),
+            }
+        }
+        'u' => {
+            if chars.next() != Some('{') {
+                return err(FormatErrorKind::InvalidUnicodeEscape);
+            }
+            let digits = parse_braced(chars)
+                .map_err(|_| FormatError::new(FormatErrorKind::InvalidUnicodeEscape))?;
+            match hex(&digits) {
+                Some(n) if !digits.is_empty() && digits.len() <= 6 => char::from_u32(n)
+                    .map_or_else(|| err(FormatErrorKind::UnicodeEscapeOutOfRange), Ok),
+                _ => err(FormatErrorKind::InvalidUnicodeEscape),
+            }
+        }
+        _ => err(FormatErrorKind::UnknownEscape(c)),
+    }
+}
+
+#[cfg(test)]
 mod tests {
-    use super::format_macipr;
-    use super::{parse_format, Format, FormatError};
+    use super::{check_format, Align, Expr, Format, FormatError, FormatErrorKind, Op, Padding};
+    use super::{format_macipr, format_macipr_with_mode};
+    use super::{Part, Radix, Row, Spec, Template};
+    use crate::addr::Addr;
+    use crate::addr_range::{AddrRange, AddrRangeSet, AddrRanges};
+    use crate::bundled_iter::BundleMode;
+    use crate::ipv4addr::IPv4Addr;
+    use crate::ipv6addr::IPv6Addr;
+    use crate::macaddr::{MacAddr, MacNotation};
+    use crate::parse_error::{ParseError, ParseErrorKind};
+    use std::str::FromStr;
+
+    /// Compares errors by message, since parse errors have spans.
+    fn parse_format(fmt_str: &str) -> Result<Vec<Spec>, String> {
+        super::parse_format(fmt_str)
+            .map(|parsed| parsed.specs)
+            .map_err(|e| e.to_string())
+    }
+
+    fn parse_formats(fmt_str: &str) -> Result<Vec<Format>, String> {
+        parse_format(fmt_str).map(|specs| specs.into_iter().map(|spec| spec.format).collect())
+    }
+
+    fn parse_paddings(fmt_str: &str) -> Result<Vec<Padding>, String> {
+        parse_format(fmt_str).map(|specs| specs.into_iter().map(|spec| spec.padding).collect())
+    }
 
     #[test]
     fn parse_format_empty() {
-        assert_eq!(parse_format(""), Ok(vec![]));
+        assert_eq!(parse_formats(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn parse_format_raw_string() {
+        assert_eq!(
+            parse_formats("foo bar"),
+            Ok(vec![Format::RawString("foo bar".to_string())])
+        );
+    }
+
+    #[test]
+    fn parse_format_percent_escape() {
+        assert_eq!(
+            parse_formats("foo %% bar"),
+            Ok(vec![Format::RawString("foo % bar".to_string())])
+        );
+    }
+
+    #[test]
+    fn parse_format_macaddr() {
+        assert_eq!(
+            parse_formats("%m"),
+            Ok(vec![Format::MacAddr {
+                notation: MacNotation::Colon,
+                upper: false,
+            }])
+        );
+    }
+
+    #[test]
+    fn parse_format_macaddr_notation() {
+        assert_eq!(
+            parse_formats("%:m%-m%.m%_m%#m"),
+            Ok(vec![
+                Format::MacAddr {
+                    notation: MacNotation::Colon,
+                    upper: false,
+                },
+                Format::MacAddr {
+                    notation: MacNotation::Hyphen,
+                    upper: false,
+                },
+                Format::MacAddr {
+                    notation: MacNotation::Dot,
+                    upper: false,
+                },
+                Format::MacAddr {
+                    notation: MacNotation::Bare,
+                    upper: false,
+                },
+                Format::MacAddr {
+                    notation: MacNotation::Hex,
+                    upper: false,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_format_macaddr_notation_err() {
+        assert_eq!(
+            parse_formats("%-i"),
+            Err("Unexpected character after MAC address notation".to_string())
+        );
+        assert_eq!(
+            parse_formats("%-.m"),
+            Err("Unexpected character after MAC address notation".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_format_reset_padding() {
+        assert_eq!(
+            parse_formats("%05n%n"),
+            Ok(vec![
+                Format::Number {
+                    radix: Radix::Decimal,
+                    upper: false,
+                },
+                Format::Number {
+                    radix: Radix::Decimal,
+                    upper: false,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_format_ipv4addr() {
+        assert_eq!(parse_formats("%i"), Ok(vec![Format::IPv4Addr]));
+    }
+
+    #[test]
+    fn parse_format_ipv6addr() {
+        assert_eq!(
+            parse_formats("%x"),
+            Ok(vec![Format::IPv6Addr { upper: false }])
+        );
+    }
+
+    #[test]
+    fn parse_format_ipv6fulladdr() {
+        assert_eq!(
+            parse_formats("%X"),
+            Ok(vec![Format::IPv6FullAddr { upper: false }])
+        );
+    }
+
+    #[test]
+    fn parse_format_ptraddr() {
+        assert_eq!(
+            parse_formats("%r%~r%R%^~R"),
+            Ok(vec![
+                Format::IPv4PtrAddr { suffix: true },
+                Format::IPv4PtrAddr { suffix: false },
+                Format::IPv6PtrAddr {
+                    upper: false,
+                    suffix: true,
+                },
+                Format::IPv6PtrAddr {
+                    upper: true,
+                    suffix: false,
+                },
+            ])
+        );
+        assert_eq!(
+            parse_formats("%~x"),
+            Err("Suffix omission is only for PTR specifier".to_string())
+        );
+        assert_eq!(
+            parse_formats("%^r"),
+            Err("Uppercase is only for hexadecimal specifier".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_format_slaacaddr() {
+        assert_eq!(
+            parse_formats("%^s{2001:db8::}%s{2001:db8:1:2::/64}"),
+            Ok(vec![
+                Format::SlaacAddr {
+                    prefix: IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0),
+                    upper: true,
+                },
+                Format::SlaacAddr {
+                    prefix: IPv6Addr::new(0x2001, 0xdb8, 1, 2, 0, 0, 0, 0),
+                    upper: false,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_format_link_local_addr() {
+        assert_eq!(
+            parse_formats("%l%^l{eth0}"),
+            Ok(vec![
+                Format::SlaacAddr {
+                    prefix: IPv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0),
+                    upper: false,
+                },
+                Format::SlaacAddr {
+                    prefix: IPv6Addr::from_str("fe80::%eth0").unwrap(),
+                    upper: true,
+                },
+            ])
+        );
+        assert_eq!(parse_formats("%l{}"), Err("Invalid zone".to_string()));
+    }
+
+    #[test]
+    fn parse_format_slaacaddr_err() {
+        assert_eq!(parse_formats("%s"), Err("Missing SLAAC prefix".to_string()));
+        assert_eq!(
+            parse_formats("%s{2001:db8::1}"),
+            Err("Invalid SLAAC prefix".to_string())
+        );
+        assert_eq!(
+            parse_formats("%s{2001:db8::/48}"),
+            Err("Invalid SLAAC prefix".to_string())
+        );
+        assert_eq!(
+            parse_formats("%s{2001:db8::"),
+            Err("Unterminated parameter".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_format_ipv4_embedded_addr() {
+        let nat64 = IPv6Addr::new(0x64, 0xff9b, 0, 0, 0, 0, 0, 0);
+        let mapped = IPv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0, 0);
+        assert_eq!(
+            parse_formats("%e%^,e{2001:db8:64::/96}%f%,x"),
+            Ok(vec![
+                Format::IPv4EmbeddedAddr {
+                    prefix: nat64,
+                    upper: false,
+                    mixed: false,
+                },
+                Format::IPv4EmbeddedAddr {
+                    prefix: IPv6Addr::new(0x2001, 0xdb8, 0x64, 0, 0, 0, 0, 0),
+                    upper: true,
+                    mixed: true,
+                },
+                Format::IPv4EmbeddedAddr {
+                    prefix: mapped,
+                    upper: false,
+                    mixed: false,
+                },
+                Format::IPv6MixedAddr { upper: false },
+            ])
+        );
+        assert_eq!(
+            parse_formats("%e{64:ff9b::1}"),
+            Err("Invalid NAT64 prefix".to_string())
+        );
+        assert_eq!(
+            parse_formats("%.x"),
+            Err("Unexpected character after MAC address notation".to_string())
+        );
+        assert_eq!(
+            parse_formats("%,X"),
+            Err("Mixed notation is only for IPv6 and embedded IPv4 specifier".to_string())
+        );
+        assert_eq!(
+            parse_formats("%,i"),
+            Err("Mixed notation is only for IPv6 and embedded IPv4 specifier".to_string())
+        );
+        assert_eq!(
+            parse_formats("%,m"),
+            Err("Mixed notation is only for IPv6 and embedded IPv4 specifier".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_format_upper() {
+        assert_eq!(
+            parse_formats("%^m%^-m%^x%^X"),
+            Ok(vec![
+                Format::MacAddr {
+                    notation: MacNotation::Colon,
+                    upper: true,
+                },
+                Format::MacAddr {
+                    notation: MacNotation::Hyphen,
+                    upper: true,
+                },
+                Format::IPv6Addr { upper: true },
+                Format::IPv6FullAddr { upper: true },
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_format_upper_err() {
+        assert_eq!(
+            parse_formats("%^i"),
+            Err("Uppercase is only for hexadecimal specifier".to_string())
+        );
+        assert_eq!(
+            parse_formats("%^5n"),
+            Err("Uppercase is only for hexadecimal specifier".to_string())
+        );
+        assert_eq!(
+            parse_formats("%^o"),
+            Err("Uppercase is only for hexadecimal specifier".to_string())
+        );
+        assert_eq!(
+            parse_formats("%-^m"),
+            Err("Unexpected character after MAC address notation".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_format_number() {
+        assert_eq!(
+            parse_formats("%n"),
+            Ok(vec![Format::Number {
+                radix: Radix::Decimal,
+                upper: false,
+            }])
+        );
+    }
+
+    #[test]
+    fn parse_format_padded_number() {
+        assert_eq!(
+            parse_paddings("%123n"),
+            Ok(vec![Padding {
+                width: 123,
+                ..Padding::default()
+            }])
+        );
+    }
+
+    #[test]
+    fn parse_format_zero_padded_number() {
+        assert_eq!(
+            parse_paddings("%09n"),
+            Ok(vec![Padding {
+                zero: true,
+                width: 9,
+                ..Padding::default()
+            }])
+        );
+    }
+
+    #[test]
+    fn parse_format_aligned() {
+        let padding = |align, width| Padding {
+            zero: false,
+            align,
+            width,
+        };
+        assert_eq!(
+            parse_paddings("%-17m%-15i%>39x%=5n%<c"),
+            Ok(vec![
+                padding(Align::Left, 17),
+                padding(Align::Left, 15),
+                padding(Align::Right, 39),
+                padding(Align::Center, 5),
+                padding(Align::Left, 0),
+            ])
+        );
+        assert_eq!(
+            parse_format("%-m%-20-m%17-m"),
+            Ok(vec![
+                Spec {
+                    format: Format::MacAddr {
+                        notation: MacNotation::Hyphen,
+                        upper: false,
+                    },
+                    arg: Some(0),
+                    expr: Expr::default(),
+                    part: None,
+                    padding: Padding::default(),
+                },
+                Spec {
+                    format: Format::MacAddr {
+                        notation: MacNotation::Hyphen,
+                        upper: false,
+                    },
+                    arg: Some(1),
+                    expr: Expr::default(),
+                    part: None,
+                    padding: padding(Align::Left, 20),
+                },
+                Spec {
+                    format: Format::MacAddr {
+                        notation: MacNotation::Hyphen,
+                        upper: false,
+                    },
+                    arg: Some(2),
+                    expr: Expr::default(),
+                    part: None,
+                    padding: padding(Align::Right, 17),
+                },
+            ])
+        );
+        assert_eq!(
+            parse_paddings("%-20{x+1}%{=20x+1}"),
+            Ok(vec![padding(Align::Left, 20), padding(Align::Center, 20)])
+        );
+    }
+
+    #[test]
+    fn parse_format_radix_number() {
+        let number = |radix, upper| Format::Number { radix, upper };
+        assert_eq!(
+            parse_formats("%h%H%^h%o%b"),
+            Ok(vec![
+                number(Radix::Hex, false),
+                number(Radix::Hex, true),
+                number(Radix::Hex, true),
+                number(Radix::Octal, false),
+                number(Radix::Binary, false),
+            ])
+        );
+        assert_eq!(
+            parse_formats("%08b"),
+            Ok(vec![Format::Number {
+                radix: Radix::Binary,
+                upper: false,
+            }])
+        );
+    }
+
+    #[test]
+    fn parse_format_padded_number_overflow() {
+        assert_eq!(
+            parse_formats("%256n"),
+            Err("Padding length overflow".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_format_positional() {
+        assert_eq!(
+            parse_format("%2$m %i %1$05n"),
+            Ok(vec![
+                Spec {
+                    format: Format::MacAddr {
+                        notation: MacNotation::Colon,
+                        upper: false,
+                    },
+                    arg: Some(1),
+                    expr: Expr::default(),
+                    part: None,
+                    padding: Padding::default(),
+                },
+                Spec::raw(" ".to_string()),
+                Spec {
+                    format: Format::IPv4Addr,
+                    arg: Some(0),
+                    expr: Expr::default(),
+                    part: None,
+                    padding: Padding::default(),
+                },
+                Spec::raw(" ".to_string()),
+                Spec {
+                    format: Format::Number {
+                        radix: Radix::Decimal,
+                        upper: false,
+                    },
+                    arg: Some(0),
+                    expr: Expr::default(),
+                    part: None,
+                    padding: Padding {
+                        zero: true,
+                        width: 5,
+                        ..Padding::default()
+                    },
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_format_positional_err() {
+        for fmt_str in &["%0$m", "%$m", "%1$2$m"] {
+            assert_eq!(
+                parse_format(fmt_str),
+                Err("Invalid argument position".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn parse_format_counter() {
+        assert_eq!(
+            parse_format("%c%03c{0}%c{0x10:2}"),
+            Ok(vec![
+                Spec {
+                    format: Format::Counter { start: 1, step: 1 },
+                    arg: None,
+                    expr: Expr::default(),
+                    part: None,
+                    padding: Padding::default(),
+                },
+                Spec {
+                    format: Format::Counter { start: 0, step: 1 },
+                    arg: None,
+                    expr: Expr::default(),
+                    part: None,
+                    padding: Padding {
+                        zero: true,
+                        width: 3,
+                        ..Padding::default()
+                    },
+                },
+                Spec {
+                    format: Format::Counter { start: 16, step: 2 },
+                    arg: None,
+                    expr: Expr::default(),
+                    part: None,
+                    padding: Padding::default(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_format_counter_err() {
+        assert_eq!(
+            parse_format("%c{1:x}"),
+            Err("Invalid counter parameter".to_string())
+        );
+        assert_eq!(
+            parse_format("%1$c"),
+            Err("Invalid argument position".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_format_expr() {
+        assert_eq!(
+            parse_format("%{i+1}%2${05n*2+3*4-1}"),
+            Ok(vec![
+                Spec {
+                    format: Format::IPv4Addr,
+                    arg: Some(0),
+                    expr: Expr {
+                        ops: vec![(Op::Add, 1)],
+                    },
+                    part: None,
+                    padding: Padding::default(),
+                },
+                Spec {
+                    format: Format::Number {
+                        radix: Radix::Decimal,
+                        upper: false,
+                    },
+                    arg: Some(1),
+                    expr: Expr {
+                        ops: vec![(Op::Mul, 2), (Op::Add, 12), (Op::Sub, 1)],
+                    },
+                    part: None,
+                    padding: Padding {
+                        zero: true,
+                        width: 5,
+                        ..Padding::default()
+                    },
+                },
+            ])
+        );
+        assert_eq!(
+            parse_format("%{n*2*3/4*5}").map(|mut specs| specs.remove(0).expr),
+            Ok(Expr {
+                ops: vec![(Op::Mul, 6), (Op::Div, 4), (Op::Mul, 5)],
+            })
+        );
+        assert_eq!(
+            parse_formats("%{^-m+0x10}"),
+            Ok(vec![Format::MacAddr {
+                notation: MacNotation::Hyphen,
+                upper: true,
+            }])
+        );
+    }
+
+    #[test]
+    fn parse_format_expr_err() {
+        let err = |msg: &str| Err(msg.to_string());
+        assert_eq!(parse_format("%{+1}"), err("Invalid expression"));
+        assert_eq!(parse_format("%{1$i+1}"), err("Invalid expression"));
+        assert_eq!(parse_format("%{i+1"), err("Unterminated parameter"));
+        assert_eq!(
+            parse_format("%{i 1}"),
+            err("Unexpected character in expression")
+        );
+        assert_eq!(parse_format("%{i+}"), err("Invalid number in expression"));
+        assert_eq!(parse_format("%{n/0}"), err("Division by zero"));
+        assert_eq!(
+            parse_format("%{i*2}"),
+            err("Multiplication and division are only for number")
+        );
+        assert_eq!(parse_format("%^{m+1}"), err("Unexpected character after %"));
+        assert_eq!(
+            parse_format("%{i+4294967296}"),
+            err("Number out of range in expression")
+        );
+        assert_eq!(
+            parse_format("%{m-0x1000000000000}"),
+            err("Number out of range in expression")
+        );
+        assert_eq!(
+            parse_format("%{n+0x10000000000000000*0x10000000000000000}"),
+            err("Number out of range in expression")
+        );
+        assert_eq!(
+            parse_format("%{n*340282366920938463463374607431768211455*2}"),
+            err("Number out of range in expression")
+        );
+        assert!(parse_format("%{i+4294967295}%{m-0xffffffffffff}%{x+1}").is_ok());
+    }
+
+    #[test]
+    fn parse_format_part() {
+        let parts = |fmt_str| {
+            parse_format(fmt_str).map(|specs| specs.into_iter().map(|spec| spec.part).collect())
+        };
+        assert_eq!(
+            parts("%i[3]%m[0..3]%X[7x]%n[1]"),
+            Ok(vec![
+                Some(Part {
+                    start: 3,
+                    end: 4,
+                    radix: None,
+                }),
+                Some(Part {
+                    start: 0,
+                    end: 3,
+                    radix: None,
+                }),
+                Some(Part {
+                    start: 7,
+                    end: 8,
+                    radix: Some(Radix::Hex),
+                }),
+                None,
+                None,
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_format_part_err() {
+        let err = |msg: &str| Err(msg.to_string());
+        assert_eq!(parse_format("%i[4]"), err("Index out of range"));
+        assert_eq!(
+            parse_format(&format!("%i[{}]", usize::MAX)),
+            err("Index out of range")
+        );
+        assert_eq!(parse_format("%m[3..3]"), err("Index out of range"));
+        assert_eq!(parse_format("%x[0..9]"), err("Index out of range"));
+        assert_eq!(parse_format("%i[a]"), err("Invalid index"));
+        assert_eq!(parse_format("%i[1..]"), err("Invalid index"));
+        assert_eq!(parse_format("%i[1"), err("Unterminated index"));
+    }
+
+    #[test]
+    fn parse_format_escape() {
+        assert_eq!(
+            parse_formats("\\n"),
+            Ok(vec![Format::RawString("\n".to_string())])
+        );
+        assert_eq!(
+            parse_formats("\\\\"),
+            Ok(vec![Format::RawString("\\".to_string())])
+        );
+        assert_eq!(
+            parse_formats("\\\\%m\\n%i\\\\foo"),
+            Ok(vec![
+                Format::RawString("\\".to_string()),
+                Format::MacAddr {
+                    notation: MacNotation::Colon,
+                    upper: false,
+                },
+                Format::RawString("\n".to_string()),
+                Format::IPv4Addr,
+                Format::RawString("\\foo".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_format_more_escape() {
+        assert_eq!(
+            parse_formats("\\t\\r\\0\\e[0m\\x41\\x7f\\u{3b1}\\u{1F600}"),
+            Ok(vec![Format::RawString(
+                "\t\r\0\x1b[0mA\x7f\u{3b1}\u{1f600}".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn parse_format_escape_err() {
+        let err = |kind, span| Err(FormatError::new(kind).with_span(span));
+        let parse = super::parse_format;
+        assert_eq!(parse("\\x4"), err(FormatErrorKind::InvalidHexEscape, 0..3));
+        assert_eq!(parse("\\xg0"), err(FormatErrorKind::InvalidHexEscape, 0..4));
+        assert_eq!(parse("\\x+1"), err(FormatErrorKind::InvalidHexEscape, 0..4));
+        assert_eq!(
+            parse("a\\x80"),
+            err(FormatErrorKind::HexEscapeOutOfRange, 1..5)
+        );
+        assert_eq!(
+            parse("\\u41"),
+            err(FormatErrorKind::InvalidUnicodeEscape, 0..3)
+        );
+        assert_eq!(
+            parse("\\u{41"),
+            err(FormatErrorKind::InvalidUnicodeEscape, 0..5)
+        );
+        assert_eq!(
+            parse("\\u{}"),
+            err(FormatErrorKind::InvalidUnicodeEscape, 0..4)
+        );
+        assert_eq!(
+            parse("\\u{1000000}"),
+            err(FormatErrorKind::InvalidUnicodeEscape, 0..11)
+        );
+        assert_eq!(
+            parse("\\u{d800}"),
+            err(FormatErrorKind::UnicodeEscapeOutOfRange, 0..8)
+        );
+        assert_eq!(
+            parse("\\u{zz}"),
+            err(FormatErrorKind::InvalidUnicodeEscape, 0..6)
+        );
+        assert_eq!(parse("foo\\"), err(FormatErrorKind::IncompleteEscape, 3..4));
+        assert_eq!(
+            parse("\\q").map_err(|e| e.to_string()),
+            Err("Unknown escape sequence \\q".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_format_error_span() {
+        let err = |kind, span| Err(FormatError::new(kind).with_span(span));
+        let parse = super::parse_format;
+        assert_eq!(parse("ab %k"), err(FormatErrorKind::UnexpectedChar, 3..5));
+        assert_eq!(
+            parse("%i %^n"),
+            err(FormatErrorKind::InvalidUppercase, 3..6)
+        );
+        assert_eq!(
+            parse("%{i*2} %m"),
+            err(FormatErrorKind::InvalidMulDiv, 0..6)
+        );
+        assert_eq!(
+            parse("%\u{e9}%m[7]"),
+            err(FormatErrorKind::UnexpectedChar, 0..3)
+        );
+        assert_eq!(
+            parse("\u{e9}%m[7]"),
+            err(FormatErrorKind::IndexOutOfRange, 2..7)
+        );
+        assert_eq!(
+            parse("%e{64:ff9b::1}"),
+            err(FormatErrorKind::InvalidPrefix("NAT64"), 0..14)
+        );
+        assert_eq!(
+            parse("%m %s"),
+            err(FormatErrorKind::MissingPrefix("SLAAC"), 3..5)
+        );
+    }
+
+    #[test]
+    fn check_format_all_errors() {
+        let errors = check_format("%i %k %^n \\x80 %{i*2} %m");
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.kind().clone(), e.span().unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                (FormatErrorKind::UnexpectedChar, 3..5),
+                (FormatErrorKind::InvalidUppercase, 6..9),
+                (FormatErrorKind::HexEscapeOutOfRange, 10..14),
+                (FormatErrorKind::InvalidMulDiv, 15..21),
+            ]
+        );
+        assert_eq!(check_format("%i %m %n"), vec![]);
+        let conflict =
+            |span| FormatError::new(FormatErrorKind::ConflictingSpecifiers(0)).with_span(span);
+        assert_eq!(
+            check_format("%i %1$m %1$x"),
+            vec![conflict(3..7), conflict(8..12)]
+        );
+        assert_eq!(
+            check_format("%i %k %1$m"),
+            vec![
+                FormatError::new(FormatErrorKind::UnexpectedChar).with_span(3..5),
+                conflict(6..10),
+            ]
+        );
+        assert_eq!(
+            check_format("%i %1$m %k"),
+            vec![
+                conflict(3..7),
+                FormatError::new(FormatErrorKind::UnexpectedChar).with_span(8..10),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_format_error() {
+        assert_eq!(
+            parse_formats("%k"),
+            Err("Unexpected character after %".to_string())
+        );
+        assert_eq!(
+            parse_formats("\\q"),
+            Err("Unknown escape sequence \\q".to_string())
+        );
+    }
+
+    fn fmt_macipr_str(fmt_str: &str, args: &[String]) -> Result<String, String> {
+        let mut v = vec![];
+        format_macipr(&mut v, fmt_str, args).map_err(|e| e.to_string())?;
+        Ok(String::from_utf8_lossy(&v).to_string())
+    }
+
+    #[test]
+    fn format_macaddr_empty_arg() {
+        let args = vec![];
+        assert_eq!(fmt_macipr_str("foo", &args), Ok("foo\n".to_string()));
+    }
+
+    #[test]
+    fn format_macaddr_mac_only() {
+        let args = vec!["00:01:02:03:04:05".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%m", &args),
+            Ok("00:01:02:03:04:05\n".to_string())
+        );
+    }
+
+    #[test]
+    fn format_macaddr_mac_and_string() {
+        let args = vec!["00:01:02:03:04:05".to_string()];
+        assert_eq!(
+            fmt_macipr_str("prefix %m", &args),
+            Ok("prefix 00:01:02:03:04:05\n".to_string())
+        );
+
+        assert_eq!(
+            fmt_macipr_str("%m postfix", &args),
+            Ok("00:01:02:03:04:05 postfix\n".to_string())
+        );
+
+        assert_eq!(
+            fmt_macipr_str("prefix %m postfix", &args),
+            Ok("prefix 00:01:02:03:04:05 postfix\n".to_string())
+        );
+    }
+
+    #[test]
+    fn format_macaddr_multiple_macs() {
+        let args = vec![
+            "00:00:00:00:00:01".to_string(),
+            "00:00:00:00:00:02".to_string(),
+            "00:00:00:00:00:03".to_string(),
+        ];
+        assert_eq!(
+            fmt_macipr_str("MAC %m, %m and %m", &args),
+            Ok("MAC 00:00:00:00:00:01, 00:00:00:00:00:02 and 00:00:00:00:00:03\n".to_string())
+        );
+    }
+
+    #[test]
+    fn format_macaddr_range_one_mac() {
+        let args = vec!["1-3".to_string()];
+        assert_eq!(
+            fmt_macipr_str("MAC=%m", &args),
+            Ok("MAC=00:00:00:00:00:01\nMAC=00:00:00:00:00:02\nMAC=00:00:00:00:00:03\n".to_string())
+        );
     }
 
     #[test]
-    fn parse_format_raw_string() {
+    fn format_macaddr_range_multiple_macs() {
+        let args = vec![
+            "1-5".to_string(),
+            "ff:ff:ff:ff:ff:00-ff:ff:ff:ff:ff:03".to_string(),
+        ];
         assert_eq!(
-            parse_format("foo bar"),
-            Ok(vec![Format::RawString("foo bar".to_string())])
+            fmt_macipr_str("%m, %m", &args),
+            Ok("\
+00:00:00:00:00:01, ff:ff:ff:ff:ff:00
+00:00:00:00:00:02, ff:ff:ff:ff:ff:01
+00:00:00:00:00:03, ff:ff:ff:ff:ff:02
+00:00:00:00:00:04, ff:ff:ff:ff:ff:03
+00:00:00:00:00:05, ff:ff:ff:ff:ff:00
+"
+            .to_string())
         );
     }
 
     #[test]
-    fn parse_format_percent_escape() {
+    fn format_macaddr_notations() {
+        let args = vec![
+            "AA-BB-CC-00-11-22".to_string(),
+            "aabb.cc00.1122".to_string(),
+            "aabbcc001122".to_string(),
+            "0xaabbcc001122".to_string(),
+            "aa:bb:cc:00:11:22".to_string(),
+        ];
         assert_eq!(
-            parse_format("foo %% bar"),
-            Ok(vec![Format::RawString("foo % bar".to_string())])
+            fmt_macipr_str("%m %-m %.m %_m %#m", &args),
+            Ok(
+                "aa:bb:cc:00:11:22 aa-bb-cc-00-11-22 aabb.cc00.1122 aabbcc001122 0xaabbcc001122\n"
+                    .to_string()
+            )
         );
     }
 
     #[test]
-    fn parse_format_macaddr() {
-        assert_eq!(parse_format("%m"), Ok(vec![Format::MacAddr]));
+    fn format_upper() {
+        let args = vec![
+            "aa:bb:cc:dd:ee:ff".to_string(),
+            "aa:bb:cc:dd:ee:ff".to_string(),
+            "fe80::abcd".to_string(),
+            "fe80::abcd".to_string(),
+        ];
+        assert_eq!(
+            fmt_macipr_str("%^m %^#m %^x %^X", &args),
+            Ok(
+                "AA:BB:CC:DD:EE:FF 0xAABBCCDDEEFF FE80::ABCD FE80:0000:0000:0000:0000:0000:0000:ABCD\n"
+                    .to_string()
+            )
+        );
     }
 
     #[test]
-    fn parse_format_ipv4addr() {
-        assert_eq!(parse_format("%i"), Ok(vec![Format::IPv4Addr]));
+    fn format_macaddr_range_step() {
+        let args = vec!["aa:bb:cc:00:00:00+64:16".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%m", &args),
+            Ok("\
+aa:bb:cc:00:00:00
+aa:bb:cc:00:00:10
+aa:bb:cc:00:00:20
+aa:bb:cc:00:00:30
+aa:bb:cc:00:00:40
+"
+            .to_string())
+        );
     }
 
     #[test]
-    fn parse_format_ipv6addr() {
-        assert_eq!(parse_format("%x"), Ok(vec![Format::IPv6Addr]));
+    fn format_ipv4addr_range_list() {
+        let args = vec![
+            "10.0.0.1,10.0.0.5-10.0.0.6,10.0.1.0/31".to_string(),
+            "1-3".to_string(),
+        ];
+        assert_eq!(
+            fmt_macipr_str("%i %n", &args),
+            Ok("\
+10.0.0.1 1
+10.0.0.5 2
+10.0.0.6 3
+10.0.1.0 1
+10.0.1.1 2
+"
+            .to_string())
+        );
     }
 
     #[test]
-    fn parse_format_ipv6fulladdr() {
-        assert_eq!(parse_format("%X"), Ok(vec![Format::IPv6FullAddr]));
+    fn format_ipv4addr_range_exclusion() {
+        let args = vec!["192.168.0.0/29!192.168.0.0-192.168.0.1!192.168.0.5".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%i", &args),
+            Ok("192.168.0.2\n192.168.0.3\n192.168.0.4\n192.168.0.6\n192.168.0.7\n".to_string())
+        );
     }
 
     #[test]
-    fn parse_format_number() {
+    fn format_product_mode() {
+        let args = vec!["10,20".to_string(), "192.168.0.1-192.168.0.3".to_string()];
+        let mut v = vec![];
         assert_eq!(
-            parse_format("%n"),
-            Ok(vec![Format::Number {
-                zero_pad: false,
-                pad_len: 0,
-            }])
+            format_macipr_with_mode(&mut v, "vlan%n %i", &args, BundleMode::Product),
+            Ok(())
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&v),
+            "\
+vlan10 192.168.0.1
+vlan10 192.168.0.2
+vlan10 192.168.0.3
+vlan20 192.168.0.1
+vlan20 192.168.0.2
+vlan20 192.168.0.3
+"
         );
     }
 
     #[test]
-    fn parse_format_padded_number() {
+    fn format_shortest_mode() {
+        let args = vec!["1-5".to_string(), "10.0.0.1-10.0.0.3".to_string()];
+        let mut v = vec![];
         assert_eq!(
-            parse_format("%123n"),
-            Ok(vec![Format::Number {
-                zero_pad: false,
-                pad_len: 123,
-            }])
+            format_macipr_with_mode(&mut v, "%m %i", &args, BundleMode::Shortest),
+            Ok(())
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&v),
+            "\
+00:00:00:00:00:01 10.0.0.1
+00:00:00:00:00:02 10.0.0.2
+00:00:00:00:00:03 10.0.0.3
+"
         );
     }
 
     #[test]
-    fn parse_format_zero_padded_number() {
+    fn format_strict_mode() {
+        let args = vec!["1-3".to_string(), "10.0.0.1-10.0.0.3".to_string()];
+        let mut v = vec![];
         assert_eq!(
-            parse_format("%09n"),
-            Ok(vec![Format::Number {
-                zero_pad: true,
-                pad_len: 9,
-            }])
+            format_macipr_with_mode(&mut v, "%m %i", &args, BundleMode::Strict),
+            Ok(())
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&v),
+            "\
+00:00:00:00:00:01 10.0.0.1
+00:00:00:00:00:02 10.0.0.2
+00:00:00:00:00:03 10.0.0.3
+"
         );
     }
 
     #[test]
-    fn parse_format_padded_number_overflow() {
+    fn format_strict_mode_err() {
+        let args = vec!["1-4".to_string(), "10.0.0.1-10.0.0.3".to_string()];
+        let mut v = vec![];
         assert_eq!(
-            parse_format("%256n"),
-            Err(FormatError {
-                msg: "Padding length overflow".to_string()
-            })
+            format_macipr_with_mode(&mut v, "%m %i", &args, BundleMode::Strict),
+            Err(FormatError::new(FormatErrorKind::DifferentLengths))
         );
+        // Rows are printed until the mismatch is found.
+        assert_eq!(String::from_utf8_lossy(&v).lines().count(), 3);
     }
 
     #[test]
-    fn parse_format_escape() {
+    fn template_reused() {
+        let template = Template::from_str("%m %i").unwrap();
+        assert_eq!(template.arg_count(), 2);
+        let mut v = vec![];
+        for args in &[["1", "10.0.0.1"], ["2-3", "10.0.0.2-10.0.0.3"]] {
+            let ranges = template.parse_args(args).unwrap();
+            assert_eq!(template.write(&mut v, ranges), Ok(()));
+        }
         assert_eq!(
-            parse_format("\\n"),
-            Ok(vec![Format::RawString("\n".to_string())])
+            String::from_utf8_lossy(&v),
+            "\
+00:00:00:00:00:01 10.0.0.1
+00:00:00:00:00:02 10.0.0.2
+00:00:00:00:00:03 10.0.0.3
+"
         );
         assert_eq!(
-            parse_format("\\\\"),
-            Ok(vec![Format::RawString("\\".to_string())])
+            template.parse_args(&["1"]).err(),
+            Some(FormatError::new(FormatErrorKind::InsufficientArguments))
         );
         assert_eq!(
-            parse_format("\\\\%m\\n%i\\\\foo"),
-            Ok(vec![
-                Format::RawString("\\".to_string()),
-                Format::MacAddr,
-                Format::RawString("\n".to_string()),
-                Format::IPv4Addr,
-                Format::RawString("\\foo".to_string()),
-            ])
+            template.parse_args(&["1", "10.0.0.1", "2"]).err(),
+            Some(FormatError::new(FormatErrorKind::UnexpectedArgument))
         );
     }
 
     #[test]
-    fn parse_format_error() {
+    fn template_typed_ranges() {
+        let mut template = Template::from_str("%m %i").unwrap();
+        template.set_mode(BundleMode::Strict);
+        let ranges = || {
+            let mut ranges = AddrRanges::<Addr>::new();
+            let macs = AddrRange::new(
+                MacAddr::new(0, 0, 0, 0, 0, 1),
+                MacAddr::new(0, 0, 0, 0, 0, 2),
+            );
+            ranges.push(AddrRangeSet::from(macs).into_range());
+            let ips = AddrRange::new(IPv4Addr::new(10, 0, 0, 1), IPv4Addr::new(10, 0, 0, 2));
+            ranges.push(AddrRangeSet::from(ips).into_range());
+            ranges
+        };
+        let mut s = String::new();
+        assert_eq!(template.render(&mut s, ranges()), Ok(()));
         assert_eq!(
-            parse_format("%k"),
-            Err(FormatError {
-                msg: "Unexpected character after %".to_string()
-            })
+            s,
+            "00:00:00:00:00:01 10.0.0.1\n00:00:00:00:00:02 10.0.0.2\n"
         );
+        let lines = template.lines(ranges()).unwrap();
         assert_eq!(
-            parse_format("\\r"),
-            Err(FormatError {
-                msg: "Unexpected character after \\".to_string()
-            })
+            lines.collect::<Result<Vec<_>, _>>(),
+            Ok(vec![
+                "00:00:00:00:00:01 10.0.0.1".to_string(),
+                "00:00:00:00:00:02 10.0.0.2".to_string(),
+            ])
+        );
+
+        let mut ranges = AddrRanges::<Addr>::new();
+        ranges.push(AddrRangeSet::from(AddrRange::new(1u128, 1)).into_range());
+        ranges.push(AddrRangeSet::from(AddrRange::new(1u128, 1)).into_range());
+        assert_eq!(
+            template.lines(ranges).unwrap().next(),
+            Some(Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                "MAC".to_string()
+            ))))
+        );
+
+        let template = Template::from_str("%i").unwrap();
+        let mut ranges = AddrRanges::<Addr>::new();
+        let macs = AddrRange::new(
+            MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff),
+            MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff),
+        );
+        ranges.push(AddrRangeSet::from(macs).into_range());
+        assert_eq!(
+            template.render(&mut String::new(), ranges),
+            Err(FormatError::new(FormatErrorKind::UnexpectedAddr(
+                "IPv4".to_string()
+            )))
         );
     }
 
-    fn fmt_macipr_str(fmt_str: &str, args: &Vec<String>) -> Result<String, FormatError> {
-        let mut v = vec![];
-        format_macipr(&mut v, fmt_str, args)?;
-        Ok(String::from_utf8_lossy(&v).to_string())
+    #[test]
+    fn template_without_args() {
+        let template = Template::from_str("%c hello").unwrap();
+        let lines = template.lines(AddrRanges::new()).unwrap();
+        assert_eq!(lines.collect::<Vec<_>>(), vec![Ok("1 hello".to_string())]);
+        let template = Template::from_str("").unwrap();
+        assert_eq!(template.lines(AddrRanges::new()).unwrap().count(), 0);
     }
 
     #[test]
-    fn format_macaddr_empty_arg() {
-        let args = vec![];
-        assert_eq!(fmt_macipr_str("foo", &args), Ok("foo\n".to_string()));
+    fn template_rows() {
+        let template = Template::from_str("%c: %-4n %i").unwrap();
+        let ranges = template.parse_args(&["1", "10.0.0.1-10.0.0.3"]).unwrap();
+        let rows = template.rows(ranges).unwrap();
+        assert_eq!(
+            rows.skip(1).collect::<Result<Vec<_>, _>>(),
+            Ok(vec![
+                Row {
+                    addrs: vec![Addr::Number(1), Addr::IPv4(IPv4Addr::new(10, 0, 0, 2))],
+                    pieces: ["2", ": ", "1   ", " ", "10.0.0.2"]
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                },
+                Row {
+                    addrs: vec![Addr::Number(1), Addr::IPv4(IPv4Addr::new(10, 0, 0, 3))],
+                    pieces: ["3", ": ", "1   ", " ", "10.0.0.3"]
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                },
+            ])
+        );
     }
 
     #[test]
-    fn format_macaddr_mac_only() {
-        let args = vec!["00:01:02:03:04:05".to_string()];
+    fn template_rows_lazy() {
+        let template = Template::from_str("%i").unwrap();
+        let ranges = template.parse_args(&["0.0.0.0/0"]).unwrap();
+        let rows = template.rows(ranges).unwrap();
+        let row = rows
+            .filter_map(Result::ok)
+            .find(|row| row.addrs()[0] == Addr::IPv4(IPv4Addr::new(0, 0, 1, 0)))
+            .unwrap();
+        assert_eq!(row.to_string(), "0.0.1.0");
+        let ranges = template.parse_args(&["0.0.0.0/0"]).unwrap();
+        let lines = template.lines(ranges).unwrap();
         assert_eq!(
-            fmt_macipr_str("%m", &args),
-            Ok("00:01:02:03:04:05\n".to_string())
+            lines.take(2).collect::<Vec<_>>(),
+            vec![Ok("0.0.0.0".to_string()), Ok("0.0.0.1".to_string())]
         );
     }
 
     #[test]
-    fn format_macaddr_mac_and_string() {
-        let args = vec!["00:01:02:03:04:05".to_string()];
+    fn template_rows_lazy_strict() {
+        let mut template = Template::from_str("%i %i").unwrap();
+        template.set_mode(BundleMode::Strict);
+        let ranges = template.parse_args(&["0.0.0.0/0", "0.0.0.0/0"]).unwrap();
+        let lines = template.lines(ranges).unwrap();
         assert_eq!(
-            fmt_macipr_str("prefix %m", &args),
-            Ok("prefix 00:01:02:03:04:05\n".to_string())
+            lines.take(2).collect::<Vec<_>>(),
+            vec![
+                Ok("0.0.0.0 0.0.0.0".to_string()),
+                Ok("0.0.0.1 0.0.0.1".to_string())
+            ]
         );
 
+        let ranges = template.parse_args(&["0.0.0.0/0", "0.0.0.0/31"]).unwrap();
+        let mut rows = template.rows(ranges).unwrap();
+        assert!(rows.next().unwrap().is_ok());
+        assert!(rows.next().unwrap().is_ok());
         assert_eq!(
-            fmt_macipr_str("%m postfix", &args),
-            Ok("00:01:02:03:04:05 postfix\n".to_string())
+            rows.next(),
+            Some(Err(FormatError::new(FormatErrorKind::DifferentLengths)))
         );
+        assert_eq!(rows.next(), None);
+    }
 
+    #[test]
+    fn format_positional() {
+        let args = vec!["aa:bb:cc:dd:ee:fe+1".to_string(), "10.0.0.1".to_string()];
         assert_eq!(
-            fmt_macipr_str("prefix %m postfix", &args),
-            Ok("prefix 00:01:02:03:04:05 postfix\n".to_string())
+            fmt_macipr_str("%2$i %1$n %1$m %1$_m", &args),
+            Ok(concat!(
+                "10.0.0.1 187723572702974 aa:bb:cc:dd:ee:fe aabbccddeefe\n",
+                "10.0.0.1 187723572702975 aa:bb:cc:dd:ee:ff aabbccddeeff\n",
+            )
+            .to_string())
+        );
+        let args = vec!["10.0.0.1".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%1$h %1$i %1$r", &args),
+            Ok("a000001 10.0.0.1 1.0.0.10.in-addr.arpa\n".to_string())
         );
     }
 
     #[test]
-    fn format_macaddr_multiple_macs() {
-        let args = vec![
-            "00:00:00:00:00:01".to_string(),
-            "00:00:00:00:00:02".to_string(),
-            "00:00:00:00:00:03".to_string(),
-        ];
+    fn format_positional_err() {
+        let args = vec!["10.0.0.1".to_string()];
         assert_eq!(
-            fmt_macipr_str("MAC %m, %m and %m", &args),
-            Ok("MAC 00:00:00:00:00:01, 00:00:00:00:00:02 and 00:00:00:00:00:03\n".to_string())
+            fmt_macipr_str("%1$i %1$m", &args),
+            Err("Conflicting specifiers for argument 1".to_string())
+        );
+        let args = vec!["1".to_string(), "2".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%2$n", &args),
+            Err("Unexpected argument".to_string())
+        );
+        assert_eq!(
+            fmt_macipr_str("%3$n %n %n", &args),
+            Err("Insufficient number of arguments".to_string())
         );
     }
 
     #[test]
-    fn format_macaddr_range_one_mac() {
-        let args = vec!["1-3".to_string()];
+    fn format_counter() {
+        let args = vec!["10.0.0.1-10.0.0.3".to_string()];
         assert_eq!(
-            fmt_macipr_str("MAC=%m", &args),
-            Ok("MAC=00:00:00:00:00:01\nMAC=00:00:00:00:00:02\nMAC=00:00:00:00:00:03\n".to_string())
+            fmt_macipr_str("host-%03c %i %c{10:-5}", &args),
+            Err("Invalid counter parameter".to_string())
         );
+        assert_eq!(
+            fmt_macipr_str("host-%03c %i %c{0:5}", &args),
+            Ok(concat!(
+                "host-001 10.0.0.1 0\n",
+                "host-002 10.0.0.2 5\n",
+                "host-003 10.0.0.3 10\n",
+            )
+            .to_string())
+        );
+        assert_eq!(fmt_macipr_str("host-%c", &[]), Ok("host-1\n".to_string()));
+        assert_eq!(fmt_macipr_str("", &[]), Ok("".to_string()));
     }
 
     #[test]
-    fn format_macaddr_range_multiple_macs() {
+    fn format_expr() {
+        let args = vec!["10.0.0.0/30h".to_string(), "aa:bb:cc:00:00:01".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%1$i gw %1${i-1} %2${m+0x10} %1${n*2/4} %{c*10}", &args),
+            Ok(concat!(
+                "10.0.0.1 gw 10.0.0.0 aa:bb:cc:00:00:11 83886080 10\n",
+                "10.0.0.2 gw 10.0.0.1 aa:bb:cc:00:00:11 83886081 20\n",
+            )
+            .to_string())
+        );
+        let args = vec!["3".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%{n-5}", &args),
+            Err("Result out of range".to_string())
+        );
+        let args = vec!["0xffffffffffffffffffffffffffffffff".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%{n*2}", &args),
+            Err("Result out of range".to_string())
+        );
+        assert_eq!(
+            fmt_macipr_str("%{c-2}", &[]),
+            Err("Result out of range".to_string())
+        );
+    }
+
+    #[test]
+    fn format_part() {
         let args = vec![
-            "1-5".to_string(),
-            "ff:ff:ff:ff:ff:00-ff:ff:ff:ff:ff:03".to_string(),
+            "192.168.0.1".to_string(),
+            "aa:bb:cc:dd:ee:ff".to_string(),
+            "2001:db8::1".to_string(),
         ];
         assert_eq!(
-            fmt_macipr_str("%m, %m", &args),
-            Ok("\
-00:00:00:00:00:01, ff:ff:ff:ff:ff:00
-00:00:00:00:00:02, ff:ff:ff:ff:ff:01
-00:00:00:00:00:03, ff:ff:ff:ff:ff:02
-00:00:00:00:00:04, ff:ff:ff:ff:ff:03
-00:00:00:00:00:05, ff:ff:ff:ff:ff:00
-"
-            .to_string())
+            fmt_macipr_str(
+                "%1$i[3] %1$i[0..2x] %2$m[0..3] %2$^-m[3..6] %2$m[2d] %3$x[1] %3$X[6..8] %3$x[7d]",
+                &args
+            ),
+            Ok("1 c0.a8 aa:bb:cc DD-EE-FF 204 db8 0000:0001 1\n".to_string())
+        );
+        let args = vec!["10.0.0.255".to_string()];
+        assert_eq!(
+            fmt_macipr_str("host-%{i+1}[2..4]", &args),
+            Ok("host-1.0\n".to_string())
         );
     }
 
@@ -426,9 +2738,29 @@ mod tests {
         let args = vec!["00:00:00-00:00:01".to_string()];
         assert_eq!(
             fmt_macipr_str("This is %m", &args),
-            Err(FormatError {
-                msg: "Invalid MAC address".to_string()
-            })
+            Err(
+                "Invalid MAC address in argument 1: wrong number of digits in group at byte 6"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn format_arg_err_position() {
+        let args = vec![
+            "192.0.2.1".to_string(),
+            "aa:bb:cc:dd:ee:ff".to_string(),
+            "aa:bb:cc:dd:ee:ff,aa:bb:cc:dd:ee:fg".to_string(),
+        ];
+        let err = format_macipr(&mut vec![], "%i %m %m", &args).unwrap_err();
+        assert_eq!(
+            err.arg_error(),
+            Some(&ParseError::new(ParseErrorKind::InvalidDigit, 34).with_arg(2))
+        );
+        assert_eq!(err.span(), None);
+        assert_eq!(
+            err.to_string(),
+            "Invalid MAC address in argument 3: invalid digit at byte 34"
         );
     }
 
@@ -436,17 +2768,13 @@ mod tests {
     fn format_macaddr_insufficient_arg_err() {
         let args = vec!["00:00:00:00:00:01".to_string()];
         assert_eq!(
-            fmt_macipr_str("This is %m", &vec![]),
-            Err(FormatError {
-                msg: "Insufficient number of arguments".to_string()
-            })
+            fmt_macipr_str("This is %m", &[]),
+            Err("Insufficient number of arguments".to_string())
         );
 
         assert_eq!(
             fmt_macipr_str("This is %m%m", &args),
-            Err(FormatError {
-                msg: "Insufficient number of arguments".to_string()
-            })
+            Err("Insufficient number of arguments".to_string())
         );
     }
 
@@ -455,9 +2783,7 @@ mod tests {
         let args = vec!["00:00:00:00:00:01".to_string()];
         assert_eq!(
             fmt_macipr_str("This is it", &args),
-            Err(FormatError {
-                msg: "Unexpected argument".to_string()
-            })
+            Err("Unexpected argument".to_string())
         );
     }
 
@@ -470,14 +2796,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn format_ipv4addr_prefix() {
+        let args = vec!["192.168.0.0/30".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%i", &args),
+            Ok("192.168.0.0\n192.168.0.1\n192.168.0.2\n192.168.0.3\n".to_string())
+        );
+    }
+
+    #[test]
+    fn format_ipv4addr_prefix_hosts() {
+        let args = vec!["10.0.0.0/29h".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%i", &args),
+            Ok("10.0.0.1\n10.0.0.2\n10.0.0.3\n10.0.0.4\n10.0.0.5\n10.0.0.6\n".to_string())
+        );
+    }
+
     #[test]
     fn format_ipv4addr_invalid_ipv4_err() {
         let args = vec!["192.168.1".to_string()];
         assert_eq!(
             fmt_macipr_str("This is %i", &args),
-            Err(FormatError {
-                msg: "Invalid IPv4 address".to_string()
-            })
+            Err("Invalid IPv4 address in argument 1: wrong number of groups at byte 9".to_string())
         );
     }
 
@@ -490,14 +2832,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn format_ipv6addr_prefix() {
+        let args = vec!["2001:db8::/126".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%x", &args),
+            Ok("2001:db8::\n2001:db8::1\n2001:db8::2\n2001:db8::3\n".to_string())
+        );
+    }
+
     #[test]
     fn format_ipv6addr_invalid_ipv6_err() {
         let args = vec!["fe80::0::0".to_string()];
         assert_eq!(
             fmt_macipr_str("This is %x", &args),
-            Err(FormatError {
-                msg: "Invalid IPv6 address".to_string()
-            })
+            Err("Invalid IPv6 address in argument 1: unexpected character at byte 7".to_string())
         );
     }
 
@@ -515,9 +2864,91 @@ mod tests {
         let args = vec!["fe80::0::0".to_string()];
         assert_eq!(
             fmt_macipr_str("This is %X", &args),
-            Err(FormatError {
-                msg: "Invalid IPv6 full address".to_string()
-            })
+            Err(
+                "Invalid IPv6 full address in argument 1: unexpected character at byte 7"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn format_ipv4ptraddr_range() {
+        let args = vec!["192.168.0.1+1".to_string(), "10.0.0.1".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%r PTR %~r", &args),
+            Ok(
+                "1.0.168.192.in-addr.arpa PTR 1.0.0.10\n2.0.168.192.in-addr.arpa PTR 1.0.0.10\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn format_ipv6ptraddr_one_ipv6() {
+        let args = vec!["2001:db8::a".to_string(), "2001:db8::a".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%R\\n%^~R", &args),
+            Ok(concat!(
+                "a.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa\n",
+                "A.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.B.D.0.1.0.0.2\n",
+            )
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn format_ptraddr_invalid_err() {
+        let args = vec!["::1".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%r", &args),
+            Err("Invalid IPv4 address in argument 1: invalid digit at byte 0".to_string())
+        );
+    }
+
+    #[test]
+    fn format_slaacaddr_range() {
+        let args = vec![
+            "00:11:22:33:44:55+1".to_string(),
+            "00:11:22:33:44:55+1".to_string(),
+        ];
+        assert_eq!(
+            fmt_macipr_str("%m %s{2001:db8::}", &args),
+            Ok(concat!(
+                "00:11:22:33:44:55 2001:db8::211:22ff:fe33:4455\n",
+                "00:11:22:33:44:56 2001:db8::211:22ff:fe33:4456\n",
+            )
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn format_link_local_addr_zone() {
+        let args = vec!["00:11:22:33:44:55".to_string(), "fe80::1%eth0".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%^l{eth0} %x", &args),
+            Ok("FE80::211:22FF:FE33:4455%eth0 fe80::1%eth0\n".to_string())
+        );
+    }
+
+    #[test]
+    fn format_ipv4_embedded_addr_range() {
+        let args = vec!["192.0.2.1+1".to_string(); 4];
+        assert_eq!(
+            fmt_macipr_str("%i %e %,e{2001:db8:64::} %f", &args),
+            Ok(concat!(
+                "192.0.2.1 64:ff9b::c000:201 2001:db8:64::192.0.2.1 ::ffff:192.0.2.1\n",
+                "192.0.2.2 64:ff9b::c000:202 2001:db8:64::192.0.2.2 ::ffff:192.0.2.2\n",
+            )
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn format_ipv6mixedaddr() {
+        let args = vec!["64:ff9b::c000:201".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%,x", &args),
+            Ok("64:ff9b::192.0.2.1\n".to_string())
         );
     }
 
@@ -539,7 +2970,7 @@ mod tests {
         );
     }
 
-   #[test]
+    #[test]
     fn format_number_one_zero_padded_number() {
         let args = vec!["1".to_string()];
         assert_eq!(
@@ -548,14 +2979,45 @@ mod tests {
         );
     }
 
-     #[test]
+    #[test]
+    fn format_aligned() {
+        let args = vec![
+            "00:00:5e:00:53:01".to_string(),
+            "192.0.2.1".to_string(),
+            "2001:db8::1".to_string(),
+            "7".to_string(),
+        ];
+        assert_eq!(
+            fmt_macipr_str("%-19m|%15i|%=15x|%<05n|%05c", &args),
+            Ok("00:00:5e:00:53:01  |      192.0.2.1|  2001:db8::1  |7    |00001\n".to_string())
+        );
+        let args = vec!["2001:db8::1%eth0".to_string()];
+        assert_eq!(
+            fmt_macipr_str("%>20x|%1$2x|%1$<10x[7]|", &args),
+            Ok("    2001:db8::1%eth0|2001:db8::1%eth0|1         |\n".to_string())
+        );
+    }
+
+    #[test]
+    fn format_number_radix() {
+        let args = vec!["250-0xfb".to_string(); 5];
+        assert_eq!(
+            fmt_macipr_str("%n %h %H %o %b", &args),
+            Ok("250 fa FA 372 11111010\n251 fb FB 373 11111011\n".to_string())
+        );
+        let args = vec!["0b1010".to_string(); 3];
+        assert_eq!(
+            fmt_macipr_str("%04h|%4^h|%012b", &args),
+            Ok("000a|   A|000000001010\n".to_string())
+        );
+    }
+
+    #[test]
     fn format_number_invalid_number() {
         let args = vec!["-10".to_string()];
         assert_eq!(
             fmt_macipr_str("This is %n", &args),
-            Err(FormatError {
-                msg: "Invalid Number".to_string()
-            })
+            Err("Invalid Number in argument 1: invalid digit at byte 0".to_string())
         );
     }
 }
@@ -3,14 +3,21 @@ use std::net::Ipv4Addr;
 use std::ops::{Add, Sub};
 use std::str::FromStr;
 
+use crate::number::parse_number;
+use crate::parse_error::{ParseError, ParseErrorKind};
+
+/// IPv4 address, which is parsed from dotted decimal or a number.
 #[derive(Debug, PartialEq, Eq, Copy, Clone, PartialOrd, Ord)]
 pub struct IPv4Addr(Ipv4Addr);
 
 impl IPv4Addr {
-    #[cfg(test)]
     pub fn new(b1: u8, b2: u8, b3: u8, b4: u8) -> Self {
         IPv4Addr(Ipv4Addr::new(b1, b2, b3, b4))
     }
+
+    pub fn octets(&self) -> [u8; 4] {
+        self.0.octets()
+    }
 }
 
 impl Display for IPv4Addr {
@@ -20,14 +27,47 @@ impl Display for IPv4Addr {
 }
 
 impl FromStr for IPv4Addr {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok(n) = u32::from_str_radix(s, 10) {
-            return Ok(IPv4Addr::from(n));
+        if !s.contains('.') {
+            return parse_number::<u32>(s).map(IPv4Addr::from);
+        }
+        Ipv4Addr::from_str(s)
+            .map(IPv4Addr)
+            .map_err(|_| dotted_quad_error(s))
+    }
+}
+
+/// Finds out why `s` is not a valid dotted-quad IPv4 address.
+pub(crate) fn dotted_quad_error(s: &str) -> ParseError {
+    let mut start = 0;
+    let mut count = 0;
+    for octet in s.split('.') {
+        let err = |kind, offset| ParseError::new(kind, start + offset);
+        if count == 4 {
+            // Points the extra dot.
+            return ParseError::new(ParseErrorKind::GroupCount, start - 1);
+        }
+        if octet.is_empty() {
+            return err(ParseErrorKind::Empty, 0);
         }
-        Ok(IPv4Addr(Ipv4Addr::from_str(s).map_err(|_| ())?))
+        if let Some(i) = octet.find(|c: char| !c.is_ascii_digit()) {
+            return err(ParseErrorKind::InvalidDigit, i);
+        }
+        if octet.len() > 1 && octet.starts_with('0') {
+            return err(ParseErrorKind::LeadingZero, 0);
+        }
+        if octet.len() > 3 || u16::from_str(octet).map_or(true, |n| n > 255) {
+            return err(ParseErrorKind::OutOfRange, 0);
+        }
+        start += octet.len() + 1;
+        count += 1;
+    }
+    if count < 4 {
+        return ParseError::new(ParseErrorKind::GroupCount, s.len());
     }
+    ParseError::new(ParseErrorKind::UnexpectedChar, 0)
 }
 
 impl From<u32> for IPv4Addr {
@@ -36,15 +76,27 @@ impl From<u32> for IPv4Addr {
     }
 }
 
-impl Into<u32> for IPv4Addr {
-    fn into(self) -> u32 {
-        self.0.into()
+impl From<Ipv4Addr> for IPv4Addr {
+    fn from(addr: Ipv4Addr) -> Self {
+        IPv4Addr(addr)
+    }
+}
+
+impl From<IPv4Addr> for Ipv4Addr {
+    fn from(addr: IPv4Addr) -> Self {
+        addr.0
+    }
+}
+
+impl From<IPv4Addr> for u32 {
+    fn from(addr: IPv4Addr) -> Self {
+        addr.0.into()
     }
 }
 
-impl Into<u64> for IPv4Addr {
-    fn into(self) -> u64 {
-        Into::<u32>::into(self.0) as u64
+impl From<IPv4Addr> for u64 {
+    fn from(addr: IPv4Addr) -> Self {
+        u32::from(addr.0) as u64
     }
 }
 
@@ -74,9 +126,30 @@ where
     }
 }
 
+pub struct IPv4PtrAddr(Ipv4Addr, bool);
+
+impl IPv4PtrAddr {
+    pub fn wrap(ipv4: IPv4Addr, suffix: bool) -> Self {
+        IPv4PtrAddr(ipv4.0, suffix)
+    }
+}
+
+impl Display for IPv4PtrAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let o = self.0.octets();
+        write!(f, "{}.{}.{}.{}", o[3], o[2], o[1], o[0])?;
+        if self.1 {
+            write!(f, ".in-addr.arpa")?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::IPv4Addr;
+    use super::{IPv4Addr, IPv4PtrAddr};
+    use crate::parse_error::{ParseError, ParseErrorKind};
+    use std::net::Ipv4Addr;
     use std::str::FromStr;
 
     #[test]
@@ -108,6 +181,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ipv4addr_std_conversion() {
+        let addr = IPv4Addr::from(Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!(addr, IPv4Addr::new(192, 0, 2, 1));
+        assert_eq!(Ipv4Addr::from(addr), Ipv4Addr::new(192, 0, 2, 1));
+    }
+
     #[test]
     fn ipv4addr_from_str() {
         assert_eq!(IPv4Addr::from_str("0.0.0.0"), Ok(IPv4Addr::new(0, 0, 0, 0)));
@@ -138,19 +218,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ipv4addr_from_str_prefixed_number() {
+        assert_eq!(
+            IPv4Addr::from_str("0xc0a80001"),
+            Ok(IPv4Addr::new(192, 168, 0, 1))
+        );
+        assert_eq!(IPv4Addr::from_str("0o377"), Ok(IPv4Addr::new(0, 0, 0, 255)));
+        assert_eq!(
+            IPv4Addr::from_str("0b100000000"),
+            Ok(IPv4Addr::new(0, 0, 1, 0))
+        );
+        assert_eq!(
+            IPv4Addr::from_str("0x100000000"),
+            Err(ParseError::new(ParseErrorKind::OutOfRange, 0))
+        );
+    }
+
     #[test]
     fn ipv4addr_from_str_err() {
-        assert_eq!(IPv4Addr::from_str("192.168.0."), Err(()));
+        assert_eq!(
+            IPv4Addr::from_str("192.168.0."),
+            Err(ParseError::new(ParseErrorKind::Empty, 10))
+        );
 
-        assert_eq!(IPv4Addr::from_str("10.0.0.256"), Err(()));
+        assert_eq!(
+            IPv4Addr::from_str("10.0.0.256"),
+            Err(ParseError::new(ParseErrorKind::OutOfRange, 7))
+        );
 
-        assert_eq!(IPv4Addr::from_str("172.a.0.1"), Err(()));
+        assert_eq!(
+            IPv4Addr::from_str("172.a.0.1"),
+            Err(ParseError::new(ParseErrorKind::InvalidDigit, 4))
+        );
 
         assert_eq!(
             IPv4Addr::from_str("4294967296"), // 0xffffffff + 1
-            Err(())
+            Err(ParseError::new(ParseErrorKind::OutOfRange, 0))
+        );
+
+        assert_eq!(
+            IPv4Addr::from_str("10.0.0"),
+            Err(ParseError::new(ParseErrorKind::GroupCount, 6))
         );
 
-        assert_eq!(IPv4Addr::from_str("192168000001"), Err(()));
+        assert_eq!(
+            IPv4Addr::from_str("10.0.0.1.2"),
+            Err(ParseError::new(ParseErrorKind::GroupCount, 8))
+        );
+
+        assert_eq!(
+            IPv4Addr::from_str("10.0.0.01"),
+            Err(ParseError::new(ParseErrorKind::LeadingZero, 7))
+        );
+
+        assert_eq!(
+            IPv4Addr::from_str("192168000001"),
+            Err(ParseError::new(ParseErrorKind::OutOfRange, 0))
+        );
+    }
+
+    #[test]
+    fn ipv4ptraddr_display() {
+        assert_eq!(
+            format!("{}", IPv4PtrAddr::wrap(IPv4Addr::new(192, 168, 0, 1), true)),
+            "1.0.168.192.in-addr.arpa"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                IPv4PtrAddr::wrap(IPv4Addr::new(192, 168, 0, 1), false)
+            ),
+            "1.0.168.192"
+        );
     }
 }
@@ -1,44 +1,191 @@
 use std::fmt::{self, Display};
-use std::net::Ipv6Addr;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::ops::{Add, Sub};
 use std::str::FromStr;
 
+use crate::ipv4addr::dotted_quad_error;
+use crate::number::parse_number;
+use crate::parse_error::{ParseError, ParseErrorKind};
+
+/// Maximum length of zone, which is enough for interface names on Linux.
+const ZONE_MAX: usize = 15;
+
+/// Zone such as `eth0` of `fe80::1%eth0`, stored inline to keep `IPv6Addr` `Copy`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, PartialOrd, Ord, Default)]
+pub struct Zone {
+    len: u8,
+    bytes: [u8; ZONE_MAX],
+}
+
+impl Zone {
+    fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+    }
+}
+
+impl Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.len > 0 {
+            write!(f, "%{}", self.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Zone {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // ':' is rejected so as not to swallow the step of a range.
+        if let Some(i) = s.find(&['%', ':'][..]) {
+            return Err(ParseError::new(ParseErrorKind::InvalidZone, i));
+        }
+        if s.is_empty() || s.len() > ZONE_MAX {
+            return Err(ParseError::new(
+                ParseErrorKind::InvalidZone,
+                s.len().min(ZONE_MAX),
+            ));
+        }
+        let mut zone = Zone {
+            len: s.len() as u8,
+            ..Default::default()
+        };
+        zone.bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(zone)
+    }
+}
+
+/// IPv6 address with optional zone, which is parsed from hextets or a number.
 #[derive(Debug, PartialEq, Eq, Copy, Clone, PartialOrd, Ord)]
-pub struct IPv6Addr(Ipv6Addr);
+pub struct IPv6Addr(Ipv6Addr, Zone);
 
 impl IPv6Addr {
-    #[cfg(test)]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(n1: u16, n2: u16, n3: u16, n4: u16, n5: u16, n6: u16, n7: u16, n8: u16) -> Self {
-        IPv6Addr(Ipv6Addr::new(n1, n2, n3, n4, n5, n6, n7, n8))
+        IPv6Addr(
+            Ipv6Addr::new(n1, n2, n3, n4, n5, n6, n7, n8),
+            Zone::default(),
+        )
+    }
+
+    pub fn segments(&self) -> [u16; 8] {
+        self.0.segments()
+    }
+
+    /// Returns the zone such as `eth0` of `fe80::1%eth0`, if any.
+    pub fn zone(&self) -> Option<&str> {
+        if self.1.len > 0 {
+            Some(self.1.as_str())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the address whose lower 64 bits are replaced by the interface identifier.
+    pub fn with_interface_id(self, iid: u64) -> Self {
+        let n = u128::from(self) & !(u64::MAX as u128) | iid as u128;
+        IPv6Addr(Ipv6Addr::from(n), self.1)
+    }
+
+    /// Returns the address whose lower 32 bits are replaced by the IPv4 address.
+    pub fn with_ipv4(self, ipv4: u32) -> Self {
+        let n = u128::from(self) & !(u32::MAX as u128) | ipv4 as u128;
+        IPv6Addr(Ipv6Addr::from(n), self.1)
     }
 }
 
 impl Display for IPv6Addr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}{}", self.0, self.1)
     }
 }
 
 impl FromStr for IPv6Addr {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok(n) = u128::from_str_radix(s, 10) {
-            return Ok(IPv6Addr::from(n));
+        let (s, zone) = match s.split_once('%') {
+            Some((s, zone)) => (s, Zone::from_str(zone).map_err(|e| e.shift(s.len() + 1))?),
+            None => (s, Zone::default()),
+        };
+        if !s.contains(':') {
+            return parse_number::<u128>(s).map(|n| IPv6Addr(Ipv6Addr::from(n), zone));
+        }
+        Ipv6Addr::from_str(s)
+            .map(|addr| IPv6Addr(addr, zone))
+            .map_err(|_| hextets_error(s))
+    }
+}
+
+/// Finds out why `s` is not a valid IPv6 address in colon-separated hextets.
+fn hextets_error(s: &str) -> ParseError {
+    let err = ParseError::new;
+    // IPv4 address may follow the last ':'.
+    let (hex, ipv4) = match s.rfind(':') {
+        Some(i) if s[i + 1..].contains('.') => (&s[..=i], Some(i + 1)),
+        _ => (s, None),
+    };
+    if let Some(i) = hex.find(|c: char| !c.is_ascii_hexdigit() && c != ':') {
+        return err(ParseErrorKind::InvalidDigit, i);
+    }
+    if let Some(i) = s.find(":::") {
+        return err(ParseErrorKind::UnexpectedChar, i + 2);
+    }
+    let compressed = s.find("::");
+    if let Some(i) = compressed.and_then(|i| s[i + 2..].find("::").map(|j| i + 2 + j)) {
+        return err(ParseErrorKind::UnexpectedChar, i);
+    }
+    if s.starts_with(':') && compressed != Some(0) {
+        return err(ParseErrorKind::UnexpectedChar, 0);
+    }
+    if ipv4.is_none() && s.ends_with(':') && !s.ends_with("::") {
+        return err(ParseErrorKind::UnexpectedChar, s.len() - 1);
+    }
+    let mut start = 0;
+    let mut count = 0;
+    for group in hex.split(':') {
+        if group.len() > 4 {
+            return err(ParseErrorKind::GroupLength, start);
+        }
+        if !group.is_empty() {
+            count += 1;
+        }
+        start += group.len() + 1;
+    }
+    if let Some(i) = ipv4 {
+        if Ipv4Addr::from_str(&s[i..]).is_err() {
+            return dotted_quad_error(&s[i..]).shift(i);
         }
-        Ok(IPv6Addr(Ipv6Addr::from_str(s).map_err(|_| ())?))
+        count += 2;
+    }
+    if count > 8 || (compressed.is_some() && count == 8) || (compressed.is_none() && count < 8) {
+        return err(ParseErrorKind::GroupCount, s.len());
     }
+    err(ParseErrorKind::UnexpectedChar, 0)
 }
 
 impl From<u128> for IPv6Addr {
     fn from(n: u128) -> Self {
-        IPv6Addr(Ipv6Addr::from(n))
+        IPv6Addr(Ipv6Addr::from(n), Zone::default())
+    }
+}
+
+impl From<Ipv6Addr> for IPv6Addr {
+    fn from(addr: Ipv6Addr) -> Self {
+        IPv6Addr(addr, Zone::default())
+    }
+}
+
+/// Drops the zone, which `std::net::Ipv6Addr` does not have.
+impl From<IPv6Addr> for Ipv6Addr {
+    fn from(addr: IPv6Addr) -> Self {
+        addr.0
     }
 }
 
-impl Into<u128> for IPv6Addr {
-    fn into(self) -> u128 {
-        self.0.into()
+impl From<IPv6Addr> for u128 {
+    fn from(addr: IPv6Addr) -> Self {
+        addr.0.into()
     }
 }
 
@@ -51,7 +198,7 @@ where
     fn add(self, rhs: N) -> Self::Output {
         let n: u128 = self.0.into();
         let (add, _) = n.overflowing_add(rhs.into());
-        IPv6Addr(Ipv6Addr::from(add))
+        IPv6Addr(Ipv6Addr::from(add), self.1)
     }
 }
 
@@ -64,32 +211,137 @@ where
     fn sub(self, rhs: N) -> Self::Output {
         let n: u128 = self.0.into();
         let (sub, _) = n.overflowing_sub(rhs.into());
-        IPv6Addr(Ipv6Addr::from(sub))
+        IPv6Addr(Ipv6Addr::from(sub), self.1)
     }
 }
 
-pub struct IPv6FullAddr(Ipv6Addr);
+/// IPv6 address printed in the compressed form, such as `2001:DB8::1` in uppercase.
+///
+/// Zone is printed as is, since it is not HEX digits.
+pub struct IPv6CompressedAddr(Ipv6Addr, Zone, bool);
+
+impl IPv6CompressedAddr {
+    pub fn wrap(ipv6: IPv6Addr, upper: bool) -> Self {
+        IPv6CompressedAddr(ipv6.0, ipv6.1, upper)
+    }
+}
+
+impl Display for IPv6CompressedAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.2 {
+            write!(f, "{}{}", self.0.to_string().to_uppercase(), self.1)
+        } else {
+            write!(f, "{}{}", self.0, self.1)
+        }
+    }
+}
+
+pub struct IPv6FullAddr(Ipv6Addr, Zone, bool);
 
 impl IPv6FullAddr {
-    pub fn wrap(ipv6: IPv6Addr) -> Self {
-        IPv6FullAddr(ipv6.0)
+    pub fn wrap(ipv6: IPv6Addr, upper: bool) -> Self {
+        IPv6FullAddr(ipv6.0, ipv6.1, upper)
     }
 }
 
 impl Display for IPv6FullAddr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let segs = self.0.segments();
-        write!(
-            f,
-            "{:04x}:{:04x}:{:04x}:{:04x}:{:04x}:{:04x}:{:04x}:{:04x}",
-            segs[0], segs[1], segs[2], segs[3], segs[4], segs[5], segs[6], segs[7],
-        )
+        for (i, seg) in self.0.segments().iter().enumerate() {
+            if i > 0 {
+                write!(f, ":")?;
+            }
+            if self.2 {
+                write!(f, "{:04X}", seg)?;
+            } else {
+                write!(f, "{:04x}", seg)?;
+            }
+        }
+        write!(f, "{}", self.1)
+    }
+}
+
+/// IPv6 address whose lower 32 bits are printed in dotted decimal, such as `64:ff9b::192.0.2.1`.
+pub struct IPv6MixedAddr(Ipv6Addr, Zone, bool);
+
+impl IPv6MixedAddr {
+    pub fn wrap(ipv6: IPv6Addr, upper: bool) -> Self {
+        IPv6MixedAddr(ipv6.0, ipv6.1, upper)
+    }
+}
+
+impl Display for IPv6MixedAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let upper = self.2;
+        let segs = &self.0.segments()[..6];
+        // The first longest run of 2 or more zero segments is compressed.
+        let mut zeros = (0, 0);
+        let mut i = 0;
+        while i < segs.len() {
+            let len = segs[i..].iter().take_while(|&&seg| seg == 0).count();
+            if len >= 2 && len > zeros.1 - zeros.0 {
+                zeros = (i, i + len);
+            }
+            i += len.max(1);
+        }
+        let write_segs = |f: &mut fmt::Formatter<'_>, segs: &[u16]| -> fmt::Result {
+            for seg in segs {
+                if upper {
+                    write!(f, "{:X}:", seg)?;
+                } else {
+                    write!(f, "{:x}:", seg)?;
+                }
+            }
+            Ok(())
+        };
+        if zeros.1 > zeros.0 {
+            write_segs(f, &segs[..zeros.0])?;
+            if zeros.0 == 0 {
+                write!(f, ":")?;
+            }
+            write!(f, ":")?;
+            write_segs(f, &segs[zeros.1..])?;
+        } else {
+            write_segs(f, segs)?;
+        }
+        let o = self.0.octets();
+        write!(f, "{}.{}.{}.{}{}", o[12], o[13], o[14], o[15], self.1)
+    }
+}
+
+/// Nibbles are printed in uppercase if `upper`, but the `ip6.arpa` suffix is kept in lowercase.
+pub struct IPv6PtrAddr(Ipv6Addr, bool, bool);
+
+impl IPv6PtrAddr {
+    pub fn wrap(ipv6: IPv6Addr, suffix: bool, upper: bool) -> Self {
+        IPv6PtrAddr(ipv6.0, suffix, upper)
+    }
+}
+
+impl Display for IPv6PtrAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = u128::from(self.0);
+        for i in 0..32 {
+            let nibble = (n >> (i * 4)) & 0xf;
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            if self.2 {
+                write!(f, "{:X}", nibble)?;
+            } else {
+                write!(f, "{:x}", nibble)?;
+            }
+        }
+        if self.1 {
+            write!(f, ".ip6.arpa")?;
+        }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{IPv6Addr, IPv6FullAddr};
+    use super::{IPv6Addr, IPv6CompressedAddr, IPv6FullAddr, IPv6MixedAddr, IPv6PtrAddr, Zone};
+    use crate::parse_error::{ParseError, ParseErrorKind};
     use std::net::Ipv6Addr;
     use std::str::FromStr;
 
@@ -109,6 +361,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ipv6addr_with_interface_id() {
+        assert_eq!(
+            IPv6Addr::new(0x2001, 0xdb8, 0, 1, 0xffff, 0, 0, 0xffff)
+                .with_interface_id(0x021122fffe334455),
+            IPv6Addr::new(0x2001, 0xdb8, 0, 1, 0x0211, 0x22ff, 0xfe33, 0x4455)
+        );
+    }
+
     #[test]
     fn ipv6addr_sub() {
         assert_eq!(
@@ -165,36 +426,252 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ipv6addr_from_str_prefixed_number() {
+        assert_eq!(
+            IPv6Addr::from_str("0x20010db8000000000000000000000001"),
+            Ok(IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1))
+        );
+        assert_eq!(
+            IPv6Addr::from_str("0o177777"),
+            Ok(IPv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0xffff))
+        );
+        assert_eq!(
+            IPv6Addr::from_str("0b11"),
+            Ok(IPv6Addr::new(0, 0, 0, 0, 0, 0, 0, 3))
+        );
+        assert_eq!(
+            IPv6Addr::from_str("0b::1"),
+            Ok(IPv6Addr::new(0xb, 0, 0, 0, 0, 0, 0, 1))
+        );
+    }
+
     #[test]
     fn ipv6addr_from_str_err() {
-        assert_eq!(IPv6Addr::from_str("::0::1"), Err(()));
+        assert_eq!(
+            IPv6Addr::from_str("::0::1"),
+            Err(ParseError::new(ParseErrorKind::UnexpectedChar, 3))
+        );
 
-        assert_eq!(IPv6Addr::from_str("::10000"), Err(()));
+        assert_eq!(
+            IPv6Addr::from_str("::10000"),
+            Err(ParseError::new(ParseErrorKind::GroupLength, 2))
+        );
 
-        assert_eq!(IPv6Addr::from_str("::fgff"), Err(()));
+        assert_eq!(
+            IPv6Addr::from_str("::fgff"),
+            Err(ParseError::new(ParseErrorKind::InvalidDigit, 3))
+        );
 
         assert_eq!(
             IPv6Addr::from_str("340282366920938463463374607431768211456"), // 0xffffffffffffffffffffffffffffffffffff + 1
-            Err(())
+            Err(ParseError::new(ParseErrorKind::OutOfRange, 0))
+        );
+
+        assert_eq!(
+            IPv6Addr::from_str("2001:db8:1:2:3:4:5"),
+            Err(ParseError::new(ParseErrorKind::GroupCount, 18))
+        );
+
+        assert_eq!(
+            IPv6Addr::from_str("2001:db8:"),
+            Err(ParseError::new(ParseErrorKind::UnexpectedChar, 8))
+        );
+
+        assert_eq!(
+            IPv6Addr::from_str("::ffff:192.0.2.256"),
+            Err(ParseError::new(ParseErrorKind::OutOfRange, 15))
         );
 
-        assert_eq!(IPv6Addr::from_str("ff801"), Err(()));
+        assert_eq!(
+            IPv6Addr::from_str("ff801"),
+            Err(ParseError::new(ParseErrorKind::InvalidDigit, 0))
+        );
     }
 
     #[test]
     fn ipv6fulladdr_display() {
         assert_eq!(
-            format!("{}", IPv6FullAddr(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0))),
+            format!(
+                "{}",
+                IPv6FullAddr::wrap(IPv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), false)
+            ),
             "0000:0000:0000:0000:0000:0000:0000:0000"
         );
         assert_eq!(
             format!(
                 "{}",
-                IPv6FullAddr(Ipv6Addr::new(
-                    0xffff, 0xfffe, 0xfffd, 0xfffc, 0xfffb, 0xfffa, 0xfff9, 0xfff8
-                ))
+                IPv6FullAddr::wrap(
+                    IPv6Addr::new(0xffff, 0xfffe, 0xfffd, 0xfffc, 0xfffb, 0xfffa, 0xfff9, 0xfff8),
+                    false
+                )
             ),
             "ffff:fffe:fffd:fffc:fffb:fffa:fff9:fff8"
         );
     }
+
+    #[test]
+    fn ipv6compressedaddr_display() {
+        let addr = IPv6Addr::new(0xfe80, 0, 0, 0, 0, 0xabcd, 0, 1);
+        assert_eq!(
+            format!("{}", IPv6CompressedAddr::wrap(addr, false)),
+            "fe80::abcd:0:1"
+        );
+        assert_eq!(
+            format!("{}", IPv6CompressedAddr::wrap(addr, true)),
+            "FE80::ABCD:0:1"
+        );
+    }
+
+    #[test]
+    fn ipv6fulladdr_display_upper() {
+        assert_eq!(
+            format!(
+                "{}",
+                IPv6FullAddr::wrap(
+                    IPv6Addr::new(0xffff, 0xfffe, 0xfffd, 0xfffc, 0xfffb, 0xfffa, 0xfff9, 0xfff8),
+                    true
+                )
+            ),
+            "FFFF:FFFE:FFFD:FFFC:FFFB:FFFA:FFF9:FFF8"
+        );
+    }
+
+    #[test]
+    fn ipv6ptraddr_display() {
+        assert_eq!(
+            format!(
+                "{}",
+                IPv6PtrAddr::wrap(
+                    IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0xabc),
+                    true,
+                    false
+                )
+            ),
+            "c.b.a.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                IPv6PtrAddr::wrap(
+                    IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0xabc),
+                    false,
+                    false
+                )
+            ),
+            "c.b.a.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2"
+        );
+    }
+
+    #[test]
+    fn ipv6ptraddr_display_upper() {
+        assert_eq!(
+            format!(
+                "{}",
+                IPv6PtrAddr::wrap(
+                    IPv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0xabc),
+                    true,
+                    true
+                )
+            ),
+            "C.B.A.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.B.D.0.1.0.0.2.ip6.arpa"
+        );
+    }
+
+    #[test]
+    fn ipv6addr_from_str_zone() {
+        let addr = IPv6Addr::from_str("fe80::1%eth0").unwrap();
+        assert_eq!(
+            addr,
+            IPv6Addr(
+                IPv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1).0,
+                Zone::from_str("eth0").unwrap()
+            )
+        );
+        assert_eq!(addr.zone(), Some("eth0"));
+        assert_eq!(format!("{}", addr), "fe80::1%eth0");
+        assert_eq!(format!("{}", addr + 1u32), "fe80::2%eth0");
+        assert_eq!(
+            format!("{}", IPv6CompressedAddr::wrap(addr, true)),
+            "FE80::1%eth0"
+        );
+        assert_eq!(
+            format!("{}", IPv6FullAddr::wrap(addr, true)),
+            "FE80:0000:0000:0000:0000:0000:0000:0001%eth0"
+        );
+        assert_eq!(
+            format!("{}", IPv6Addr::from_str("0x1%123456789012345").unwrap()),
+            "::1%123456789012345"
+        );
+    }
+
+    #[test]
+    fn ipv6addr_from_str_zone_err() {
+        assert_eq!(
+            IPv6Addr::from_str("fe80::1%"),
+            Err(ParseError::new(ParseErrorKind::InvalidZone, 8))
+        );
+        assert_eq!(
+            IPv6Addr::from_str("fe80::1%1234567890123456"),
+            Err(ParseError::new(ParseErrorKind::InvalidZone, 23))
+        );
+        assert_eq!(
+            IPv6Addr::from_str("fe80::1%eth0%eth1"),
+            Err(ParseError::new(ParseErrorKind::InvalidZone, 12))
+        );
+        assert_eq!(
+            IPv6Addr::from_str("fe80::1%eth0:1"),
+            Err(ParseError::new(ParseErrorKind::InvalidZone, 12))
+        );
+    }
+
+    #[test]
+    fn ipv6addr_std_conversion() {
+        let std_addr = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+        let addr = IPv6Addr::from(std_addr);
+        assert_eq!(addr, IPv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1));
+        assert_eq!(addr.zone(), None);
+        let addr = IPv6Addr::from_str("fe80::1%eth0").unwrap();
+        assert_eq!(Ipv6Addr::from(addr), std_addr);
+    }
+
+    #[test]
+    fn ipv6addr_with_ipv4() {
+        assert_eq!(
+            IPv6Addr::new(0x64, 0xff9b, 0, 0, 0, 0, 0xffff, 0xffff).with_ipv4(0xc0000201),
+            IPv6Addr::new(0x64, 0xff9b, 0, 0, 0, 0, 0xc000, 0x201)
+        );
+    }
+
+    #[test]
+    fn ipv6mixedaddr_display() {
+        let mixed = |s| {
+            format!(
+                "{}",
+                IPv6MixedAddr::wrap(IPv6Addr::from_str(s).unwrap(), false)
+            )
+        };
+        assert_eq!(mixed("::"), "::0.0.0.0");
+        assert_eq!(mixed("::ffff:c000:201"), "::ffff:192.0.2.1");
+        assert_eq!(mixed("64:ff9b::c000:201"), "64:ff9b::192.0.2.1");
+        assert_eq!(mixed("2001:db8:0:1::c000:201"), "2001:db8:0:1::192.0.2.1");
+        assert_eq!(mixed("1:0:2:0:0:3:c000:201"), "1:0:2::3:192.0.2.1");
+        assert_eq!(mixed("1:0:0:2:0:0:c000:201"), "1::2:0:0:192.0.2.1");
+        assert_eq!(mixed("1:2:3:4:5:6:c000:201"), "1:2:3:4:5:6:192.0.2.1");
+        assert_eq!(mixed("fe80::c000:201%eth0"), "fe80::192.0.2.1%eth0");
+    }
+
+    #[test]
+    fn ipv6mixedaddr_display_upper() {
+        assert_eq!(
+            format!(
+                "{}",
+                IPv6MixedAddr::wrap(
+                    IPv6Addr::new(0xabcd, 0, 0, 0, 0, 0xffff, 0xc000, 0x201),
+                    true
+                )
+            ),
+            "ABCD::FFFF:192.0.2.1"
+        );
+    }
 }
@@ -5,5 +5,20 @@ mod format;
 mod ipv4addr;
 mod ipv6addr;
 mod macaddr;
+mod number;
+mod parse_error;
 
-pub use format::format_macipr;
+pub use addr::Addr;
+pub use addr_range::{
+    AddrRange, AddrRangeIter, AddrRangeList, AddrRangeListIter, AddrRangeSet, AddrRangeSetIter,
+    AddrRanges, Rangeable,
+};
+pub use bundled_iter::{BundleMode, BundledIter, IterBundle, ResettableIterator};
+pub use format::{
+    check_format, format_macipr, format_macipr_with_mode, FormatError, FormatErrorKind, Lines, Row,
+    Rows, Template,
+};
+pub use ipv4addr::IPv4Addr;
+pub use ipv6addr::IPv6Addr;
+pub use macaddr::{MacAddr, MacNotation, MacNotationAddr};
+pub use parse_error::{ParseError, ParseErrorKind};
@@ -3,8 +3,12 @@ use std::fmt::{self, Display};
 use std::ops::{Add, Sub};
 use std::str::FromStr;
 
-const MAC_MAX: u64 = 0xffffffffffffu64;
+use crate::number::parse_number;
+use crate::parse_error::{ParseError, ParseErrorKind};
 
+pub const MAC_MAX: u64 = 0xffffffffffffu64;
+
+/// MAC address, which is parsed from any notation of `MacNotation` or a number.
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub struct MacAddr {
     bytes: [u8; 6],
@@ -16,6 +20,26 @@ impl MacAddr {
             bytes: [b1, b2, b3, b4, b5, b6],
         }
     }
+
+    /// Returns the address of the number, or `None` if it does not fit in 48 bits.
+    pub fn from_u64(n: u64) -> Option<Self> {
+        if n > MAC_MAX {
+            None
+        } else {
+            Some(MacAddr::from(n))
+        }
+    }
+
+    pub fn bytes(&self) -> [u8; 6] {
+        self.bytes
+    }
+
+    /// Returns the modified EUI-64 interface identifier, which has `ff:fe` inserted
+    /// in the middle and the universal/local bit flipped.
+    pub fn modified_eui64(self) -> u64 {
+        let b = self.bytes;
+        u64::from_be_bytes([b[0] ^ 0x02, b[1], b[2], 0xff, 0xfe, b[3], b[4], b[5]])
+    }
 }
 
 impl Display for MacAddr {
@@ -52,33 +76,59 @@ impl PartialOrd for MacAddr {
 }
 
 impl FromStr for MacAddr {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        if let Ok(n) = u64::from_str_radix(value, 10) {
-            if n <= MAC_MAX {
-                return Ok(MacAddr::from(n));
-            }
-        }
-        if value.len() != 17 {
-            return Err(());
-        }
-        let mut v = vec![];
-        for chunk in value.split(":") {
-            if chunk.len() != 2 {
-                return Err(());
-            }
-            if let Ok(n) = u8::from_str_radix(chunk, 16) {
-                v.push(n);
-            } else {
-                return Err(());
-            }
+        let prefixed = matches!(
+            value.get(0..2),
+            Some("0x") | Some("0X") | Some("0o") | Some("0O") | Some("0b") | Some("0B")
+        );
+        // Separators come first, since the first byte may look like a prefix such as `0b`.
+        let n = if value.contains(':') {
+            parse_groups(value, ':', 2)?
+        } else if value.contains('-') {
+            parse_groups(value, '-', 2)?
+        } else if value.contains('.') {
+            parse_groups(value, '.', 4)?
+        } else if prefixed {
+            parse_number::<u64>(value)?
+        } else if value.len() == 12 {
+            // Even if it consists of decimal digits only, it is read as HEX like other notations.
+            parse_hex(value)?
+        } else {
+            parse_number::<u64>(value)?
+        };
+        MacAddr::from_u64(n).ok_or_else(|| ParseError::new(ParseErrorKind::OutOfRange, 0))
+    }
+}
+
+fn parse_hex(value: &str) -> Result<u64, ParseError> {
+    if let Some(i) = value.find(|c: char| !c.is_ascii_hexdigit()) {
+        return Err(ParseError::new(ParseErrorKind::InvalidDigit, i));
+    }
+    u64::from_str_radix(value, 16).map_err(|_| ParseError::new(ParseErrorKind::OutOfRange, 0))
+}
+
+/// Parses 12 HEX digits separated by `sep` into groups of `group_len` digits.
+fn parse_groups(value: &str, sep: char, group_len: usize) -> Result<u64, ParseError> {
+    let mut n = 0;
+    let mut start = 0;
+    let mut count = 0;
+    for group in value.split(sep) {
+        if count == 12 / group_len {
+            return Err(ParseError::new(ParseErrorKind::GroupCount, start - 1));
         }
-        if v.len() != 6 {
-            return Err(());
+        if group.len() != group_len {
+            return Err(ParseError::new(ParseErrorKind::GroupLength, start));
         }
-        Ok(MacAddr::new(v[0], v[1], v[2], v[3], v[4], v[5]))
+        n = (n << (group_len * 4)) | parse_hex(group).map_err(|e| e.shift(start))?;
+        start += group.len() + 1;
+        count += 1;
+    }
+    if count < 12 / group_len {
+        return Err(ParseError::new(ParseErrorKind::GroupCount, value.len()));
     }
+    Ok(n)
 }
 
 impl<N> Add<N> for MacAddr
@@ -89,7 +139,7 @@ where
 
     fn add(self, rhs: N) -> Self::Output {
         let n: u64 = self.into();
-        MacAddr::from(n + rhs.into() & MAC_MAX)
+        MacAddr::from(n.wrapping_add(rhs.into()))
     }
 }
 
@@ -101,36 +151,105 @@ where
 
     fn sub(self, rhs: N) -> Self::Output {
         let n: u64 = self.into();
-        let (sub, _) = n.overflowing_sub(rhs.into());
-        MacAddr::from(sub & MAC_MAX)
+        MacAddr::from(n.wrapping_sub(rhs.into()))
     }
 }
 
-impl Into<u64> for MacAddr {
-    fn into(self) -> u64 {
+impl From<MacAddr> for u64 {
+    fn from(addr: MacAddr) -> Self {
         let mut n: u64 = 0;
-        for b in &self.bytes {
+        for b in &addr.bytes {
             n = (n << 8u64) + *b as u64;
         }
         n
     }
 }
 
+/// Bits above 48 are dropped, so that arithmetic wraps around. Use `MacAddr::from_u64`
+/// to reject such numbers.
 impl From<u64> for MacAddr {
     fn from(n: u64) -> Self {
         let mut bytes: [u8; 6] = [0; 6];
-        for i in 0..6 {
-            bytes[i] = (n >> ((5 - i) * 8) & 0xff) as u8;
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = (n >> ((5 - i) * 8) & 0xff) as u8;
         }
         MacAddr { bytes }
     }
 }
 
+/// Notation of MAC address on display.
+#[derive(Debug, PartialEq, Copy, Clone)]
+#[non_exhaustive]
+pub enum MacNotation {
+    /// `aa:bb:cc:dd:ee:ff`
+    Colon,
+    /// `aa-bb-cc-dd-ee-ff`
+    Hyphen,
+    /// `aabb.ccdd.eeff`
+    Dot,
+    /// `aabbccddeeff`
+    Bare,
+    /// `0xaabbccddeeff`
+    Hex,
+}
+
+/// MAC address printed in the notation.
+pub struct MacNotationAddr(MacAddr, MacNotation, bool);
+
+impl MacNotationAddr {
+    pub fn wrap(mac: MacAddr, notation: MacNotation, upper: bool) -> Self {
+        MacNotationAddr(mac, notation, upper)
+    }
+
+    fn write_groups(&self, f: &mut fmt::Formatter<'_>, sep: &str, group_len: usize) -> fmt::Result {
+        for (i, b) in self.0.bytes.iter().enumerate() {
+            if i > 0 && i % group_len == 0 {
+                f.write_str(sep)?;
+            }
+            if self.2 {
+                write!(f, "{:02X}", b)?;
+            } else {
+                write!(f, "{:02x}", b)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for MacNotationAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            MacNotation::Colon => self.write_groups(f, ":", 1),
+            MacNotation::Hyphen => self.write_groups(f, "-", 1),
+            MacNotation::Dot => self.write_groups(f, ".", 2),
+            MacNotation::Bare => self.write_groups(f, "", 6),
+            MacNotation::Hex => {
+                f.write_str("0x")?;
+                self.write_groups(f, "", 6)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::MacAddr;
+    use super::{MacAddr, MacNotation, MacNotationAddr};
+    use crate::parse_error::{ParseError, ParseErrorKind};
     use std::str::FromStr;
 
+    #[test]
+    fn mac_addr_from_u64() {
+        assert_eq!(
+            MacAddr::from_u64(0xffffffffffff),
+            Some(MacAddr::new(0xff, 0xff, 0xff, 0xff, 0xff, 0xff))
+        );
+        assert_eq!(MacAddr::from_u64(0x1000000000000), None);
+        assert_eq!(
+            MacAddr::from(0x1000000000001),
+            MacAddr::new(0, 0, 0, 0, 0, 1)
+        );
+    }
+
     #[test]
     fn mac_addr_display() {
         let mac1 = MacAddr::new(0, 1, 2, 3, 4, 5);
@@ -140,6 +259,18 @@ mod tests {
         assert_eq!(format!("{}", mac2), "aa:bb:cc:dd:ee:ff");
     }
 
+    #[test]
+    fn mac_addr_modified_eui64() {
+        assert_eq!(
+            MacAddr::new(0x00, 0x11, 0x22, 0x33, 0x44, 0x55).modified_eui64(),
+            0x021122fffe334455
+        );
+        assert_eq!(
+            MacAddr::new(0x02, 0x11, 0x22, 0x33, 0x44, 0x55).modified_eui64(),
+            0x001122fffe334455
+        );
+    }
+
     #[test]
     fn mac_addr_ordering() {
         let mac1 = MacAddr::new(0, 1, 2, 3, 4, 5);
@@ -209,17 +340,207 @@ mod tests {
 
     #[test]
     fn mac_addr_from_str_err() {
-        assert_eq!(MacAddr::from_str("00:11:22:33:44:5"), Err(()));
+        assert_eq!(
+            MacAddr::from_str("00:11:22:33:44:5"),
+            Err(ParseError::new(ParseErrorKind::GroupLength, 15))
+        );
 
-        assert_eq!(MacAddr::from_str("aa:bb:cc:dd:ee:0ff"), Err(()));
+        assert_eq!(
+            MacAddr::from_str("aa:bb:cc:dd:ee:0ff"),
+            Err(ParseError::new(ParseErrorKind::GroupLength, 15))
+        );
 
-        assert_eq!(MacAddr::from_str("aa:bb:cc:dd:ee:fg"), Err(()));
+        assert_eq!(
+            MacAddr::from_str("aa:bb:cc:dd:ee:fg"),
+            Err(ParseError::new(ParseErrorKind::InvalidDigit, 16))
+        );
 
         assert_eq!(
             MacAddr::from_str("281474976710656"), // 0xffffffffffff + 1
-            Err(())
+            Err(ParseError::new(ParseErrorKind::OutOfRange, 0))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("aabbccddeefg"),
+            Err(ParseError::new(ParseErrorKind::InvalidDigit, 11))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("aa:bb-cc:dd-ee:ff"),
+            Err(ParseError::new(ParseErrorKind::GroupLength, 3))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("aa-bb-cc-dd-ee-f"),
+            Err(ParseError::new(ParseErrorKind::GroupLength, 15))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("aa:bb:cc:dd:ee"),
+            Err(ParseError::new(ParseErrorKind::GroupCount, 14))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("aa:bb:cc:dd:ee:ff:00"),
+            Err(ParseError::new(ParseErrorKind::GroupCount, 17))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("aab.bccd.deeff"),
+            Err(ParseError::new(ParseErrorKind::GroupLength, 0))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("aabb:ccdd:eeff"),
+            Err(ParseError::new(ParseErrorKind::GroupLength, 0))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("0x1000000000000"),
+            Err(ParseError::new(ParseErrorKind::OutOfRange, 0))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("0x"),
+            Err(ParseError::new(ParseErrorKind::Empty, 2))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("0x+1"),
+            Err(ParseError::new(ParseErrorKind::InvalidDigit, 2))
+        );
+
+        assert_eq!(
+            MacAddr::from_str("+a:bb:cc:dd:ee:ff"),
+            Err(ParseError::new(ParseErrorKind::InvalidDigit, 0))
+        );
+    }
+
+    #[test]
+    fn mac_addr_from_str_notations() {
+        let mac = MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff);
+        assert_eq!(MacAddr::from_str("AA:BB:CC:DD:EE:FF"), Ok(mac));
+        assert_eq!(MacAddr::from_str("aa-bb-cc-dd-ee-ff"), Ok(mac));
+        assert_eq!(MacAddr::from_str("AA-BB-CC-DD-EE-FF"), Ok(mac));
+        assert_eq!(MacAddr::from_str("aabb.ccdd.eeff"), Ok(mac));
+        assert_eq!(MacAddr::from_str("AABB.CCDD.EEFF"), Ok(mac));
+        assert_eq!(MacAddr::from_str("aabbccddeeff"), Ok(mac));
+        assert_eq!(
+            MacAddr::from_str("001122334455"),
+            Ok(MacAddr::new(0, 0x11, 0x22, 0x33, 0x44, 0x55))
+        );
+        assert_eq!(MacAddr::from_str("0xaabbccddeeff"), Ok(mac));
+        assert_eq!(MacAddr::from_str("0XAABBCCDDEEFF"), Ok(mac));
+        assert_eq!(MacAddr::from_str("0x1"), Ok(MacAddr::new(0, 0, 0, 0, 0, 1)));
+        assert_eq!(
+            MacAddr::from_str("0x0000aabbccddeeff"),
+            Ok(MacAddr::new(0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff))
+        );
+    }
+
+    #[test]
+    fn mac_addr_from_str_prefixed_number() {
+        assert_eq!(
+            MacAddr::from_str("0o777"),
+            Ok(MacAddr::new(0, 0, 0, 0, 0x01, 0xff))
+        );
+        assert_eq!(
+            MacAddr::from_str("0b1111"),
+            Ok(MacAddr::new(0, 0, 0, 0, 0, 0x0f))
+        );
+        assert_eq!(
+            MacAddr::from_str("0b0000000001"),
+            Ok(MacAddr::new(0, 0, 0, 0, 0, 0x01))
+        );
+        assert_eq!(
+            MacAddr::from_str("0x0000000001"),
+            Ok(MacAddr::new(0, 0, 0, 0, 0, 0x01))
+        );
+        assert_eq!(
+            MacAddr::from_str("0xaabbccddee"),
+            Ok(MacAddr::new(0, 0xaa, 0xbb, 0xcc, 0xdd, 0xee))
+        );
+        assert_eq!(
+            MacAddr::from_str("0o1234567012"),
+            Ok(MacAddr::new(0, 0, 0x0a, 0x72, 0xee, 0x0a))
+        );
+        assert_eq!(
+            MacAddr::from_str("0b0b0b0b0b0b"),
+            Err(ParseError::new(ParseErrorKind::InvalidDigit, 3))
+        );
+        assert_eq!(
+            MacAddr::from_str("0b2"),
+            Err(ParseError::new(ParseErrorKind::InvalidDigit, 2))
+        );
+    }
+
+    #[test]
+    fn mac_addr_from_str_prefix_like_first_byte() {
+        let mac = MacAddr::new(0x0b, 0, 0, 0, 0, 1);
+        assert_eq!(MacAddr::from_str("0b:00:00:00:00:01"), Ok(mac));
+        assert_eq!(MacAddr::from_str("0b-00-00-00-00-01"), Ok(mac));
+        assert_eq!(MacAddr::from_str("0b00.0000.0001"), Ok(mac));
+        assert_eq!(
+            MacAddr::from_str("0bcd.ef01.2345"),
+            Ok(MacAddr::new(0x0b, 0xcd, 0xef, 0x01, 0x23, 0x45))
         );
+        assert_eq!(
+            MacAddr::from_str("0B:11:22:33:44:55"),
+            Ok(MacAddr::new(0x0b, 0x11, 0x22, 0x33, 0x44, 0x55))
+        );
+        assert_eq!(
+            MacAddr::from_str("0x:11:22:33:44:55"),
+            Err(ParseError::new(ParseErrorKind::InvalidDigit, 1))
+        );
+    }
 
-        assert_eq!(MacAddr::from_str("aabbccddeeff"), Err(()));
+    #[test]
+    fn mac_notation_addr_display() {
+        let mac = MacAddr::new(0, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e);
+        assert_eq!(
+            format!("{}", MacNotationAddr::wrap(mac, MacNotation::Colon, false)),
+            "00:1a:2b:3c:4d:5e"
+        );
+        assert_eq!(
+            format!("{}", MacNotationAddr::wrap(mac, MacNotation::Hyphen, false)),
+            "00-1a-2b-3c-4d-5e"
+        );
+        assert_eq!(
+            format!("{}", MacNotationAddr::wrap(mac, MacNotation::Dot, false)),
+            "001a.2b3c.4d5e"
+        );
+        assert_eq!(
+            format!("{}", MacNotationAddr::wrap(mac, MacNotation::Bare, false)),
+            "001a2b3c4d5e"
+        );
+        assert_eq!(
+            format!("{}", MacNotationAddr::wrap(mac, MacNotation::Hex, false)),
+            "0x001a2b3c4d5e"
+        );
+    }
+
+    #[test]
+    fn mac_notation_addr_display_upper() {
+        let mac = MacAddr::new(0, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e);
+        assert_eq!(
+            format!("{}", MacNotationAddr::wrap(mac, MacNotation::Colon, true)),
+            "00:1A:2B:3C:4D:5E"
+        );
+        assert_eq!(
+            format!("{}", MacNotationAddr::wrap(mac, MacNotation::Hyphen, true)),
+            "00-1A-2B-3C-4D-5E"
+        );
+        assert_eq!(
+            format!("{}", MacNotationAddr::wrap(mac, MacNotation::Dot, true)),
+            "001A.2B3C.4D5E"
+        );
+        assert_eq!(
+            format!("{}", MacNotationAddr::wrap(mac, MacNotation::Bare, true)),
+            "001A2B3C4D5E"
+        );
+        assert_eq!(
+            format!("{}", MacNotationAddr::wrap(mac, MacNotation::Hex, true)),
+            "0x001A2B3C4D5E"
+        );
     }
 }
@@ -1,9 +1,13 @@
 use std::env::args;
 use std::error::Error;
 use std::io::{stdout, BufWriter};
+use std::ops::Range;
 use std::process::exit;
+use std::str::FromStr;
 
-use macipr::format_macipr;
+use macipr::{check_format, format_macipr_with_mode, BundleMode, FormatError};
+
+const USAGE: &str = "usage: macipr [-m MODE] FORMAT [ARG..]";
 
 fn main() {
     if let Err(err) = macipr(args()) {
@@ -16,13 +20,50 @@ fn macipr<I>(mut args: I) -> Result<(), Box<dyn Error>>
 where
     I: Iterator<Item = String>,
 {
-    let format = match args.nth(1) {
-        Some(format) => format,
-        None => {
-            return Err("usage: macipr FORMAT [MAC..]")?;
+    let mut mode = BundleMode::default();
+    args.next();
+    let format = loop {
+        match args.next() {
+            Some(arg) if arg == "-m" || arg == "--mode" => {
+                let value = args.next().ok_or(USAGE)?;
+                mode = BundleMode::from_str(&value)
+                    .map_err(|_| format!("macipr: Invalid mode: {}", value))?;
+            }
+            Some(arg) if arg == "--" => break args.next().ok_or(USAGE)?,
+            Some(format) => break format,
+            None => return Err(USAGE.into()),
         }
     };
+    let args = args.collect::<Vec<_>>();
     let mut writer = BufWriter::new(stdout());
-    Ok(format_macipr(&mut writer, &format, &args.collect())
-        .map_err(|e| format!("macipr: {}", e))?)
+    Ok(format_macipr_with_mode(&mut writer, &format, &args, mode)
+        .map_err(|e| error_message(&e, &format, &args))?)
+}
+
+/// Shows the invalid part of the format string or the argument with carets under it.
+/// Every problem in the format string is shown at once.
+fn error_message(err: &FormatError, format: &str, args: &[String]) -> String {
+    if err.span().is_some() {
+        return check_format(format)
+            .iter()
+            .map(|e| match e.span() {
+                Some(span) => format!("macipr: {}{}", e, highlight(format, span)),
+                None => format!("macipr: {}", e),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    let mut msg = format!("macipr: {}", err);
+    if let Some(e) = err.arg_error() {
+        if let Some(arg) = e.arg().and_then(|i| args.get(i)) {
+            msg += &highlight(arg, e.offset()..e.offset() + 1);
+        }
+    }
+    msg
+}
+
+fn highlight(s: &str, span: Range<usize>) -> String {
+    let column = s.get(..span.start).map_or(0, |s| s.chars().count());
+    let width = s.get(span).map_or(1, |s| s.chars().count().max(1));
+    format!("\n  {}\n  {}{}", s, " ".repeat(column), "^".repeat(width))
 }
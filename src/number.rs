@@ -0,0 +1,95 @@
+use std::convert::TryFrom;
+
+use crate::parse_error::{ParseError, ParseErrorKind};
+
+/// Parses decimal number, or hexadecimal, octal and binary number prefixed by
+/// `0x`, `0o` and `0b` respectively.
+pub fn parse_number<N>(value: &str) -> Result<N, ParseError>
+where
+    N: TryFrom<u128>,
+{
+    let (digits, radix, start) = match value.get(0..2) {
+        Some("0x") | Some("0X") => (&value[2..], 16, 2),
+        Some("0o") | Some("0O") => (&value[2..], 8, 2),
+        Some("0b") | Some("0B") => (&value[2..], 2, 2),
+        _ => (value, 10, 0),
+    };
+    if digits.is_empty() {
+        return Err(ParseError::new(ParseErrorKind::Empty, start));
+    }
+    if let Some(i) = digits.find(|c: char| !c.is_digit(radix)) {
+        return Err(ParseError::new(ParseErrorKind::InvalidDigit, start + i));
+    }
+    let out_of_range = || ParseError::new(ParseErrorKind::OutOfRange, 0);
+    let n = u128::from_str_radix(digits, radix).map_err(|_| out_of_range())?;
+    N::try_from(n).map_err(|_| out_of_range())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_number;
+    use crate::parse_error::{ParseError, ParseErrorKind};
+
+    fn err<T>(kind: ParseErrorKind, offset: usize) -> Result<T, ParseError> {
+        Err(ParseError::new(kind, offset))
+    }
+
+    #[test]
+    fn parse_number_decimal() {
+        assert_eq!(parse_number::<u32>("0"), Ok(0));
+        assert_eq!(parse_number::<u32>("4294967295"), Ok(u32::MAX));
+        assert_eq!(parse_number::<u8>("0255"), Ok(255));
+    }
+
+    #[test]
+    fn parse_number_prefixed() {
+        assert_eq!(parse_number::<u64>("0xff"), Ok(255));
+        assert_eq!(parse_number::<u64>("0XFF"), Ok(255));
+        assert_eq!(parse_number::<u64>("0o17"), Ok(15));
+        assert_eq!(parse_number::<u64>("0O17"), Ok(15));
+        assert_eq!(parse_number::<u64>("0b101"), Ok(5));
+        assert_eq!(parse_number::<u64>("0B101"), Ok(5));
+        assert_eq!(
+            parse_number::<u128>("0xffffffffffffffffffffffffffffffff"),
+            Ok(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn parse_number_err() {
+        assert_eq!(parse_number::<u32>(""), err(ParseErrorKind::Empty, 0));
+        assert_eq!(parse_number::<u32>("0x"), err(ParseErrorKind::Empty, 2));
+        assert_eq!(
+            parse_number::<u32>("+1"),
+            err(ParseErrorKind::InvalidDigit, 0)
+        );
+        assert_eq!(
+            parse_number::<u32>("-1"),
+            err(ParseErrorKind::InvalidDigit, 0)
+        );
+        assert_eq!(
+            parse_number::<u32>("0xfg"),
+            err(ParseErrorKind::InvalidDigit, 3)
+        );
+        assert_eq!(
+            parse_number::<u32>("0o8"),
+            err(ParseErrorKind::InvalidDigit, 2)
+        );
+        assert_eq!(
+            parse_number::<u32>("0b2"),
+            err(ParseErrorKind::InvalidDigit, 2)
+        );
+        assert_eq!(
+            parse_number::<u32>("1a"),
+            err(ParseErrorKind::InvalidDigit, 1)
+        );
+        assert_eq!(
+            parse_number::<u32>("0x100000000"),
+            err(ParseErrorKind::OutOfRange, 0)
+        );
+        assert_eq!(
+            parse_number::<u128>("0x100000000000000000000000000000000"),
+            err(ParseErrorKind::OutOfRange, 0)
+        );
+    }
+}
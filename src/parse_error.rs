@@ -0,0 +1,114 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// Why an address, a number or a range could not be parsed.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The value or a part of it is empty.
+    Empty,
+    /// A character which is not a digit of the expected radix.
+    InvalidDigit,
+    /// A decimal octet with a leading zero, such as `010`.
+    LeadingZero,
+    /// Too many or too few groups, such as octets of IPv4 address.
+    GroupCount,
+    /// A group with wrong number of digits, such as `a` of `aa:bb:cc:dd:ee:f`.
+    GroupLength,
+    /// A number which does not fit in the address or the group.
+    OutOfRange,
+    /// A misplaced separator or other character which is not expected.
+    UnexpectedChar,
+    /// An IPv6 zone which is empty, too long or has `%` or `:`.
+    InvalidZone,
+    /// An end address of `START-END` whose IPv6 zone differs from the start address.
+    ZoneMismatch,
+    /// A prefix length which is not a number.
+    InvalidPrefix,
+    /// A prefix length which is longer than the address, such as `33` of `10.0.0.0/33`.
+    PrefixOutOfRange,
+    /// An offset of `START+OFFSET` which is not a number or is out of range.
+    InvalidOffset,
+    /// A step of `RANGE:STEP` which is not a number or is zero.
+    InvalidStep,
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            ParseErrorKind::Empty => "empty value",
+            ParseErrorKind::InvalidDigit => "invalid digit",
+            ParseErrorKind::LeadingZero => "leading zero",
+            ParseErrorKind::GroupCount => "wrong number of groups",
+            ParseErrorKind::GroupLength => "wrong number of digits in group",
+            ParseErrorKind::OutOfRange => "out of range",
+            ParseErrorKind::UnexpectedChar => "unexpected character",
+            ParseErrorKind::InvalidZone => "invalid zone",
+            ParseErrorKind::ZoneMismatch => "zone differs from start address",
+            ParseErrorKind::InvalidPrefix => "invalid prefix length",
+            ParseErrorKind::PrefixOutOfRange => "prefix length out of range",
+            ParseErrorKind::InvalidOffset => "invalid offset",
+            ParseErrorKind::InvalidStep => "invalid step",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+/// Error of parsing an argument, which tells what is wrong and where.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    offset: usize,
+    arg: Option<usize>,
+}
+
+impl ParseError {
+    pub(crate) fn new(kind: ParseErrorKind, offset: usize) -> Self {
+        ParseError {
+            kind,
+            offset,
+            arg: None,
+        }
+    }
+
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Byte offset of the offending part in the parsed value.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Zero-based index of the offending argument, if parsed as an argument of format.
+    pub fn arg(&self) -> Option<usize> {
+        self.arg
+    }
+
+    /// Converts the offset in a substring to the one in the string containing it.
+    pub(crate) fn shift(self, start: usize) -> Self {
+        ParseError {
+            offset: self.offset + start,
+            ..self
+        }
+    }
+
+    pub(crate) fn with_kind(self, kind: ParseErrorKind) -> Self {
+        ParseError { kind, ..self }
+    }
+
+    pub(crate) fn with_arg(self, arg: usize) -> Self {
+        ParseError {
+            arg: Some(arg),
+            ..self
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.offset)
+    }
+}
+
+impl Error for ParseError {}